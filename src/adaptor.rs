@@ -0,0 +1,188 @@
+use crate::babyjubjub::{random_scalar, BabyJubjubField};
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::prime_field::{modulus, Encode, Error, Order, PrimeField, BN_0};
+use crate::schnorr::{self, ChallengeHash, SchnorrSignature};
+use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+
+// Schnorr adaptor signatures for the signatures of `schnorr`. A pre-signature
+// (R, s') under the adaptor point T = t * G satisfies s' * G = R + e * A with
+// e = H(R + T, A, m). Whoever knows t completes it into the ordinary signature
+// (R + T, s' + t), and anyone holding both learns t = s - s'.
+//
+// In a swap, Alice pre-signs her payment with Bob's adaptor point T, Bob
+// pre-signs his payment with the same T, Alice checks it with `pre_verify`.
+// Bob claims Alice's payment with `adapt`, publishing a signature from which
+// Alice runs `extract` to get t and complete Bob's pre-signature.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreSignature {
+    pub r: BabyJubjubPoint,
+    pub s: BigInt,
+}
+
+impl PreSignature {
+    pub fn encode(&self) -> [u8; 64] {
+        let mut encode = [0u8; 64];
+        encode[..32].copy_from_slice(&self.r.encode());
+        encode[32..].copy_from_slice(&BabyJubjubField::new(&self.s).encode());
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        let signature = SchnorrSignature::decode(encode)?;
+        Ok(PreSignature {
+            r: signature.r,
+            s: signature.s,
+        })
+    }
+}
+
+pub fn adaptor_point(t: &BigInt) -> BabyJubjubPoint {
+    schnorr::public_key(t)
+}
+
+// The nonce must never be reused with the same key.
+pub fn pre_sign_with_nonce<H: ChallengeHash>(
+    msg: &H::Message,
+    secret: &BigInt,
+    adaptor_point: &BabyJubjubPoint,
+    nonce: &BigInt,
+) -> PreSignature {
+    let l = BabyJubjubField::suborder();
+    let secret = modulus(secret, l);
+    let nonce = modulus(nonce, l);
+
+    let r = BabyJubjubPoint::get_basepoint() * &nonce;
+    let e = H::challenge(&(&r + adaptor_point), &schnorr::public_key(&secret), msg);
+    let s = (nonce + e * secret) % l;
+    PreSignature { r, s }
+}
+
+pub fn pre_sign_with_rng<H: ChallengeHash, R: RngCore + CryptoRng + ?Sized>(
+    msg: &H::Message,
+    secret: &BigInt,
+    adaptor_point: &BabyJubjubPoint,
+    rng: &mut R,
+) -> PreSignature {
+    pre_sign_with_nonce::<H>(msg, secret, adaptor_point, &random_scalar(rng).v)
+}
+
+#[cfg(feature = "std")]
+pub fn pre_sign<H: ChallengeHash>(
+    msg: &H::Message,
+    secret: &BigInt,
+    adaptor_point: &BabyJubjubPoint,
+) -> PreSignature {
+    pre_sign_with_rng::<H, _>(msg, secret, adaptor_point, &mut rand::thread_rng())
+}
+
+pub fn pre_verify<H: ChallengeHash>(
+    msg: &H::Message,
+    pre_signature: &PreSignature,
+    public_key: &BabyJubjubPoint,
+    adaptor_point: &BabyJubjubPoint,
+) -> bool {
+    if pre_signature.s < *BN_0 || &pre_signature.s >= BabyJubjubField::suborder() {
+        return false;
+    }
+
+    let e = H::challenge(&(&pre_signature.r + adaptor_point), public_key, msg);
+    BabyJubjubPoint::get_basepoint() * &pre_signature.s == &pre_signature.r + public_key * e
+}
+
+pub fn adapt(pre_signature: &PreSignature, t: &BigInt) -> SchnorrSignature {
+    let l = BabyJubjubField::suborder();
+    let t = modulus(t, l);
+    SchnorrSignature {
+        r: &pre_signature.r + adaptor_point(&t),
+        s: (&pre_signature.s + t) % l,
+    }
+}
+
+// Returns t when `signature` is the adaptation of `pre_signature`.
+pub fn extract(
+    pre_signature: &PreSignature,
+    signature: &SchnorrSignature,
+    adaptor_point: &BabyJubjubPoint,
+) -> Option<BigInt> {
+    let t = modulus(&(&signature.s - &pre_signature.s), BabyJubjubField::suborder());
+    let t_point = schnorr::public_key(&t);
+    if &t_point == adaptor_point && signature.r == &pre_signature.r + &t_point {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schnorr::{PoseidonChallenge, Sha256Challenge};
+
+    #[test]
+    fn test_swap() {
+        let alice = BigInt::from(1111u64);
+        let bob = BigInt::from(2222u64);
+        let t = BigInt::from(0xbeef_u64);
+        let t_point = adaptor_point(&t);
+
+        let alice_tx = b"alice pays bob on L2";
+        let bob_tx = b"bob pays alice on L1";
+        let pre_a = pre_sign_with_nonce::<Sha256Challenge>(alice_tx, &alice, &t_point, &BigInt::from(5u64));
+        let pre_b = pre_sign_with_nonce::<Sha256Challenge>(bob_tx, &bob, &t_point, &BigInt::from(6u64));
+        assert!(pre_verify::<Sha256Challenge>(alice_tx, &pre_a, &schnorr::public_key(&alice), &t_point));
+        assert!(pre_verify::<Sha256Challenge>(bob_tx, &pre_b, &schnorr::public_key(&bob), &t_point));
+
+        // a pre-signature is not a valid signature
+        let as_signature = SchnorrSignature {
+            r: pre_a.r.clone(),
+            s: pre_a.s.clone(),
+        };
+        assert!(!schnorr::verify::<Sha256Challenge>(alice_tx, &as_signature, &schnorr::public_key(&alice)));
+
+        let sig_a = adapt(&pre_a, &t);
+        assert!(schnorr::verify::<Sha256Challenge>(alice_tx, &sig_a, &schnorr::public_key(&alice)));
+
+        let extracted = extract(&pre_a, &sig_a, &t_point).unwrap();
+        assert_eq!(extracted, t);
+        let sig_b = adapt(&pre_b, &extracted);
+        assert!(schnorr::verify::<Sha256Challenge>(bob_tx, &sig_b, &schnorr::public_key(&bob)));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let secret = BigInt::from(31337u64);
+        let pk = schnorr::public_key(&secret);
+        let t = BigInt::from(77u64);
+        let t_point = adaptor_point(&t);
+        let m = BabyJubjubField::new(&BigInt::from(9u64));
+
+        let pre = pre_sign_with_nonce::<PoseidonChallenge>(&m, &secret, &t_point, &BigInt::from(3u64));
+        assert!(pre_verify::<PoseidonChallenge>(&m, &pre, &pk, &t_point));
+        assert!(!pre_verify::<PoseidonChallenge>(&m, &pre, &pk, &adaptor_point(&BigInt::from(78u64))));
+
+        let sig = adapt(&pre, &BigInt::from(78u64));
+        assert!(!schnorr::verify::<PoseidonChallenge>(&m, &sig, &pk));
+        assert_eq!(extract(&pre, &sig, &t_point), None);
+
+        assert_eq!(PreSignature::decode(&pre.encode()).unwrap(), pre);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_pre_sign_with_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let secret = BigInt::from(31337u64);
+        let t = BigInt::from(77u64);
+        let t_point = adaptor_point(&t);
+
+        let pre = pre_sign_with_rng::<Sha256Challenge, _>(b"m", &secret, &t_point, &mut StdRng::seed_from_u64(1));
+        assert_eq!(pre, pre_sign_with_rng::<Sha256Challenge, _>(b"m", &secret, &t_point, &mut StdRng::seed_from_u64(1)));
+        assert!(pre_verify::<Sha256Challenge>(b"m", &pre, &schnorr::public_key(&secret), &t_point));
+        assert_ne!(pre, pre_sign::<Sha256Challenge>(b"m", &secret, &t_point));
+        assert!(schnorr::verify::<Sha256Challenge>(b"m", &adapt(&pre, &t), &schnorr::public_key(&secret)));
+    }
+}
@@ -1,5 +1,6 @@
 pub use crate::prime_field::*;
 use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
 
 lazy_static! {
     pub static ref ORDER: BigInt = BigInt::parse_bytes(
@@ -26,6 +27,11 @@ impl Order for BabyJubjubField {
     }
 }
 
+// Uniform in [1, suborder), for secret scalars and nonces.
+pub fn random_scalar<R: RngCore + CryptoRng + ?Sized>(rng: &mut R) -> BabyJubjubField {
+    BabyJubjubField::random_with(rng, &BN_1, BabyJubjubField::suborder())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,4 +142,31 @@ mod tests {
         );
         assert_eq!(BabyJubjubField::decode(&a.encode()), a);
     }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn babyjubjub_random() {
+        use num_traits::ToPrimitive;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let a = random_scalar(&mut StdRng::seed_from_u64(1));
+        assert_eq!(a, random_scalar(&mut StdRng::seed_from_u64(1)));
+        assert_ne!(a, random_scalar(&mut StdRng::seed_from_u64(2)));
+
+        // [10, 13) draws from 2 bits and rejects 3
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut seen = [0u32; 3];
+        for _ in 0..300 {
+            let v = random_range(&mut rng, &BigInt::from(10), &BigInt::from(13));
+            assert!(v >= BigInt::from(10) && v < BigInt::from(13));
+            seen[(v - BigInt::from(10)).to_usize().unwrap()] += 1;
+        }
+        assert!(seen.iter().all(|&n| n > 60));
+
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..100 {
+            let v = random_scalar(&mut rng).v;
+            assert!(v >= *BN_1 && v < *BabyJubjubField::suborder());
+        }
+    }
 }
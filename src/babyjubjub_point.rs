@@ -10,6 +10,8 @@ pub type BabyJubjubPoint = Point<BabyJubjubField>;
 
 mod add;
 mod mul;
+mod neg;
+mod sub;
 
 impl PartialEq for BabyJubjubPoint {
     fn eq(&self, other: &Self) -> bool {
@@ -0,0 +1,21 @@
+use super::*;
+use sp_std::ops::Neg;
+
+impl<'a> Neg for &'a BabyJubjubPoint {
+    type Output = BabyJubjubPoint;
+
+    fn neg(self) -> BabyJubjubPoint {
+        BabyJubjubPoint {
+            x: BabyJubjubField::new(&BN_0) - &self.x,
+            y: self.y.clone(),
+        }
+    }
+}
+
+impl Neg for BabyJubjubPoint {
+    type Output = BabyJubjubPoint;
+
+    fn neg(self) -> BabyJubjubPoint {
+        -&self
+    }
+}
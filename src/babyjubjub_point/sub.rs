@@ -0,0 +1,34 @@
+use super::*;
+use sp_std::ops::Sub;
+
+impl<'a, 'b> Sub<&'b BabyJubjubPoint> for &'a BabyJubjubPoint {
+    type Output = BabyJubjubPoint;
+
+    fn sub(self, other: &BabyJubjubPoint) -> BabyJubjubPoint {
+        self + &(-other)
+    }
+}
+
+impl<'b> Sub<&'b BabyJubjubPoint> for BabyJubjubPoint {
+    type Output = BabyJubjubPoint;
+
+    fn sub(self, other: &BabyJubjubPoint) -> BabyJubjubPoint {
+        &self - other
+    }
+}
+
+impl<'a> Sub<BabyJubjubPoint> for &'a BabyJubjubPoint {
+    type Output = BabyJubjubPoint;
+
+    fn sub(self, other: BabyJubjubPoint) -> BabyJubjubPoint {
+        self - &other
+    }
+}
+
+impl Sub<BabyJubjubPoint> for BabyJubjubPoint {
+    type Output = BabyJubjubPoint;
+
+    fn sub(self, other: BabyJubjubPoint) -> BabyJubjubPoint {
+        &self - &other
+    }
+}
@@ -0,0 +1,323 @@
+use crate::babyjubjub::{random_scalar, BabyJubjubField};
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::prime_field::{modulus, random_range, Error, Order, BN_0};
+use crate::schnorr::{self, ChallengeHash, SchnorrSignature};
+use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+
+// Blind Schnorr signatures, producing signatures that `schnorr::verify`
+// accepts. The signer never sees the message nor the final signature:
+//
+// 1. signer: R = k * G, sends R (`commit`),
+// 2. user: R' = R + alpha * G + beta * X, e' = H(R', X, m), sends
+//    e = e' + beta (`blind`),
+// 3. signer: sends s = k + e * x (`SignerSession::sign`),
+// 4. user: checks s, the signature is (R', s + alpha) (`UserSession::unblind`).
+//
+// WARNING: the plain protocol is only secure when each signing key runs one
+// session at a time. With about log2(l) = 251 sessions open concurrently the
+// ROS attack (Benhamouda, Lepoint, Loss, Orru, Raykova 2021) forges one more
+// signature than were issued, in polynomial time. Signers that cannot
+// serialise sessions must use the clause mode below (Fuchsbauer, Plouviez and
+// Seurin 2020): the signer commits to two nonces, the user blinds a challenge
+// for each, and the signer answers only one of them, chosen at random. It costs
+// twice the commitments and challenges and has no known ROS style attack.
+
+// Deliberately neither Clone nor Copy: a nonce must answer a single challenge.
+pub struct SignerSession {
+    k: BigInt,
+}
+
+pub struct UserSession {
+    public_key: BabyJubjubPoint,
+    r: BabyJubjubPoint,
+    r_blinded: BabyJubjubPoint,
+    alpha: BigInt,
+    e: BigInt,
+}
+
+pub fn commit_with(nonce: &BigInt) -> (SignerSession, BabyJubjubPoint) {
+    let k = modulus(nonce, BabyJubjubField::suborder());
+    let r = BabyJubjubPoint::get_basepoint() * &k;
+    (SignerSession { k }, r)
+}
+
+pub fn commit_with_rng<R: RngCore + CryptoRng + ?Sized>(rng: &mut R) -> (SignerSession, BabyJubjubPoint) {
+    commit_with(&random_scalar(rng).v)
+}
+
+#[cfg(feature = "std")]
+pub fn commit() -> (SignerSession, BabyJubjubPoint) {
+    commit_with_rng(&mut rand::thread_rng())
+}
+
+impl SignerSession {
+    // s = k + e * x
+    pub fn sign(self, secret: &BigInt, challenge: &BigInt) -> BigInt {
+        let l = BabyJubjubField::suborder();
+        (self.k + modulus(challenge, l) * modulus(secret, l)) % l
+    }
+}
+
+// `alpha` and `beta` must be uniformly random and secret, they are what makes
+// the final signature unlinkable to the session.
+pub fn blind_with<H: ChallengeHash>(
+    msg: &H::Message,
+    public_key: &BabyJubjubPoint,
+    commitment: &BabyJubjubPoint,
+    alpha: &BigInt,
+    beta: &BigInt,
+) -> (UserSession, BigInt) {
+    let l = BabyJubjubField::suborder();
+    let alpha = modulus(alpha, l);
+    let beta = modulus(beta, l);
+
+    let r_blinded = commitment + BabyJubjubPoint::get_basepoint() * &alpha + public_key * &beta;
+    let e = (H::challenge(&r_blinded, public_key, msg) + beta) % l;
+    let session = UserSession {
+        public_key: public_key.clone(),
+        r: commitment.clone(),
+        r_blinded,
+        alpha,
+        e: e.clone(),
+    };
+    (session, e)
+}
+
+pub fn blind_with_rng<H: ChallengeHash, R: RngCore + CryptoRng + ?Sized>(
+    msg: &H::Message,
+    public_key: &BabyJubjubPoint,
+    commitment: &BabyJubjubPoint,
+    rng: &mut R,
+) -> (UserSession, BigInt) {
+    let alpha = random_range(rng, &BN_0, BabyJubjubField::suborder());
+    let beta = random_range(rng, &BN_0, BabyJubjubField::suborder());
+    blind_with::<H>(msg, public_key, commitment, &alpha, &beta)
+}
+
+#[cfg(feature = "std")]
+pub fn blind<H: ChallengeHash>(
+    msg: &H::Message,
+    public_key: &BabyJubjubPoint,
+    commitment: &BabyJubjubPoint,
+) -> (UserSession, BigInt) {
+    blind_with_rng::<H, _>(msg, public_key, commitment, &mut rand::thread_rng())
+}
+
+impl UserSession {
+    pub fn unblind(self, s: &BigInt) -> Result<SchnorrSignature, Error> {
+        let l = BabyJubjubField::suborder();
+        if s < &BN_0 || s >= l {
+            return Err(Error::InvalidScalar);
+        }
+        if BabyJubjubPoint::get_basepoint() * s != &self.r + &self.public_key * &self.e {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(SchnorrSignature {
+            r: self.r_blinded,
+            s: (s + self.alpha) % l,
+        })
+    }
+}
+
+// Clause mode, the ROS mitigation. The session types wrap one plain session
+// per clause.
+
+pub struct ClauseSignerSession {
+    sessions: [SignerSession; 2],
+}
+
+pub struct ClauseUserSession {
+    sessions: [UserSession; 2],
+}
+
+pub fn clause_commit_with(nonces: &[BigInt; 2]) -> (ClauseSignerSession, [BabyJubjubPoint; 2]) {
+    let (s0, r0) = commit_with(&nonces[0]);
+    let (s1, r1) = commit_with(&nonces[1]);
+    (ClauseSignerSession { sessions: [s0, s1] }, [r0, r1])
+}
+
+pub fn clause_commit_with_rng<R: RngCore + CryptoRng + ?Sized>(
+    rng: &mut R,
+) -> (ClauseSignerSession, [BabyJubjubPoint; 2]) {
+    clause_commit_with(&[random_scalar(rng).v, random_scalar(rng).v])
+}
+
+#[cfg(feature = "std")]
+pub fn clause_commit() -> (ClauseSignerSession, [BabyJubjubPoint; 2]) {
+    clause_commit_with_rng(&mut rand::thread_rng())
+}
+
+impl ClauseSignerSession {
+    // `clause` must be chosen uniformly at random by the signer after the
+    // challenges are received, and is sent along with the response.
+    pub fn sign(self, secret: &BigInt, challenges: &[BigInt; 2], clause: bool) -> BigInt {
+        let [s0, s1] = self.sessions;
+        if clause {
+            s1.sign(secret, &challenges[1])
+        } else {
+            s0.sign(secret, &challenges[0])
+        }
+    }
+
+    pub fn sign_random_with_rng<R: RngCore + CryptoRng + ?Sized>(
+        self,
+        secret: &BigInt,
+        challenges: &[BigInt; 2],
+        rng: &mut R,
+    ) -> (bool, BigInt) {
+        let clause = rng.next_u32() & 1 == 1;
+        (clause, self.sign(secret, challenges, clause))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn sign_random(self, secret: &BigInt, challenges: &[BigInt; 2]) -> (bool, BigInt) {
+        self.sign_random_with_rng(secret, challenges, &mut rand::thread_rng())
+    }
+}
+
+// `blinding` holds (alpha, beta) for each clause, all uniformly random and
+// secret.
+pub fn clause_blind_with<H: ChallengeHash>(
+    msg: &H::Message,
+    public_key: &BabyJubjubPoint,
+    commitments: &[BabyJubjubPoint; 2],
+    blinding: &[(BigInt, BigInt); 2],
+) -> (ClauseUserSession, [BigInt; 2]) {
+    let (u0, e0) = blind_with::<H>(msg, public_key, &commitments[0], &blinding[0].0, &blinding[0].1);
+    let (u1, e1) = blind_with::<H>(msg, public_key, &commitments[1], &blinding[1].0, &blinding[1].1);
+    (ClauseUserSession { sessions: [u0, u1] }, [e0, e1])
+}
+
+pub fn clause_blind_with_rng<H: ChallengeHash, R: RngCore + CryptoRng + ?Sized>(
+    msg: &H::Message,
+    public_key: &BabyJubjubPoint,
+    commitments: &[BabyJubjubPoint; 2],
+    rng: &mut R,
+) -> (ClauseUserSession, [BigInt; 2]) {
+    let l = BabyJubjubField::suborder();
+    let mut random = || random_range(rng, &BN_0, l);
+    let blinding = [(random(), random()), (random(), random())];
+    clause_blind_with::<H>(msg, public_key, commitments, &blinding)
+}
+
+#[cfg(feature = "std")]
+pub fn clause_blind<H: ChallengeHash>(
+    msg: &H::Message,
+    public_key: &BabyJubjubPoint,
+    commitments: &[BabyJubjubPoint; 2],
+) -> (ClauseUserSession, [BigInt; 2]) {
+    clause_blind_with_rng::<H, _>(msg, public_key, commitments, &mut rand::thread_rng())
+}
+
+impl ClauseUserSession {
+    pub fn unblind(self, clause: bool, s: &BigInt) -> Result<SchnorrSignature, Error> {
+        let [u0, u1] = self.sessions;
+        if clause {
+            u1.unblind(s)
+        } else {
+            u0.unblind(s)
+        }
+    }
+}
+
+pub fn verify<H: ChallengeHash>(
+    msg: &H::Message,
+    signature: &SchnorrSignature,
+    public_key: &BabyJubjubPoint,
+) -> bool {
+    schnorr::verify::<H>(msg, signature, public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prime_field::PrimeField;
+    use crate::schnorr::{PoseidonChallenge, Sha256Challenge};
+
+    #[test]
+    fn test_blind_sign() {
+        let secret = BigInt::from(4242u64);
+        let pk = schnorr::public_key(&secret);
+        let voucher = b"voucher #1";
+
+        let (signer, r) = commit_with(&BigInt::from(17u64));
+        let (user, e) = blind_with::<Sha256Challenge>(voucher, &pk, &r, &BigInt::from(5u64), &BigInt::from(6u64));
+        let s = signer.sign(&secret, &e);
+        let sig = user.unblind(&s).unwrap();
+
+        assert!(verify::<Sha256Challenge>(voucher, &sig, &pk));
+        assert!(!verify::<Sha256Challenge>(b"voucher #2", &sig, &pk));
+        // the signer's view (R, e, s) does not appear in the signature
+        assert_ne!(sig.r, r);
+        assert_ne!(sig.s, s);
+    }
+
+    #[test]
+    fn test_bad_response() {
+        let secret = BigInt::from(4242u64);
+        let pk = schnorr::public_key(&secret);
+        let m = BabyJubjubField::new(&BigInt::from(1u64));
+
+        let (signer, r) = commit_with(&BigInt::from(17u64));
+        let (user, e) = blind_with::<PoseidonChallenge>(&m, &pk, &r, &BigInt::from(5u64), &BigInt::from(6u64));
+        let s = signer.sign(&BigInt::from(4243u64), &e);
+        assert_eq!(user.unblind(&s).err(), Some(Error::InvalidSignature));
+    }
+
+    #[test]
+    fn test_clause_mode() {
+        let secret = BigInt::from(99u64);
+        let pk = schnorr::public_key(&secret);
+        let m = BabyJubjubField::new(&BigInt::from(123u64));
+
+        for clause in [false, true].iter() {
+            let (signer, rs) = clause_commit_with(&[BigInt::from(11u64), BigInt::from(12u64)]);
+            let blinding = [
+                (BigInt::from(1u64), BigInt::from(2u64)),
+                (BigInt::from(3u64), BigInt::from(4u64)),
+            ];
+            let (user, es) = clause_blind_with::<PoseidonChallenge>(&m, &pk, &rs, &blinding);
+            let s = signer.sign(&secret, &es, *clause);
+            let sig = user.unblind(*clause, &s).unwrap();
+            assert!(verify::<PoseidonChallenge>(&m, &sig, &pk));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_random() {
+        let secret = BigInt::from(77u64);
+        let pk = schnorr::public_key(&secret);
+
+        let (signer, rs) = clause_commit();
+        let (user, es) = clause_blind::<Sha256Challenge>(b"m", &pk, &rs);
+        let (clause, s) = signer.sign_random(&secret, &es);
+        assert!(verify::<Sha256Challenge>(b"m", &user.unblind(clause, &s).unwrap(), &pk));
+
+        let (signer, r) = commit();
+        let (user, e) = blind::<Sha256Challenge>(b"m", &pk, &r);
+        assert!(verify::<Sha256Challenge>(b"m", &user.unblind(&signer.sign(&secret, &e)).unwrap(), &pk));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_with_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let secret = BigInt::from(77u64);
+        let pk = schnorr::public_key(&secret);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let (signer, rs) = clause_commit_with_rng(&mut rng);
+        let (user, es) = clause_blind_with_rng::<Sha256Challenge, _>(b"m", &pk, &rs, &mut rng);
+        let (clause, s) = signer.sign_random_with_rng(&secret, &es, &mut rng);
+        assert!(verify::<Sha256Challenge>(b"m", &user.unblind(clause, &s).unwrap(), &pk));
+
+        let (signer, r) = commit_with_rng(&mut rng);
+        assert_eq!(commit_with_rng(&mut StdRng::seed_from_u64(1)).1, rs[0]);
+        let (user, e) = blind_with_rng::<Sha256Challenge, _>(b"m", &pk, &r, &mut rng);
+        assert!(verify::<Sha256Challenge>(b"m", &user.unblind(&signer.sign(&secret, &e)).unwrap(), &pk));
+    }
+}
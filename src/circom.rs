@@ -0,0 +1,214 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::key::Sign;
+use crate::prime_field::{Encode, Error, Order, PrimeField};
+use alloc::string::{String, ToString};
+use num_bigint::BigInt;
+use sp_std::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+// Inputs of a circom EdDSA verifier for a signature of `BabyJubjub::sign`, with
+// the signal names of circomlib's EdDSA templates. Field elements are decimal
+// strings, as snarkjs reads them.
+//
+// None of the stock circomlib verifiers accepts these signatures: EdDSAVerifier
+// takes its challenge from a Pedersen hash over packed points, the MiMC and
+// Poseidon verifiers from MiMC and Poseidon, while `sign` takes it from SHA-256
+// over R8 || A || msg. The circuit has to recompute that challenge itself, see
+// `eddsa_poseidon_input` for signatures circomlib verifies as they are.
+//
+// M is the message as a little endian integer, only defined for messages of at
+// most 31 bytes so it never wraps around the field. `msg` holds the message
+// bits, least significant bit of each byte first, like circomlib's buffer2bits.
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EdDSAInput {
+    pub Ax: String,
+    pub Ay: String,
+    pub R8x: String,
+    pub R8y: String,
+    pub S: String,
+    pub M: String,
+    pub msg: Vec<u8>,
+}
+
+fn decimal(v: &BigInt) -> String {
+    v.to_string()
+}
+
+pub fn message_bits(msg: &[u8]) -> Vec<u8> {
+    msg.iter()
+        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1))
+        .collect()
+}
+
+// Longer messages are rejected instead of being reduced, two messages must never
+// map to the same M.
+pub fn message_to_field(msg: &[u8]) -> Result<BabyJubjubField, Error> {
+    if msg.len() > 31 {
+        return Err(Error::InvalidLength);
+    }
+    Ok(BabyJubjubField::new(&BigInt::from_bytes_le(num_bigint::Sign::Plus, msg)))
+}
+
+pub fn eddsa_input(
+    msg: &[u8],
+    signature: &Sign<BabyJubjubField>,
+    public_key: &BabyJubjubPoint,
+) -> Result<EdDSAInput, Error> {
+    Ok(EdDSAInput {
+        Ax: decimal(&public_key.x.v),
+        Ay: decimal(&public_key.y.v),
+        R8x: decimal(&signature.r.x.v),
+        R8y: decimal(&signature.r.y.v),
+        S: decimal(&signature.s.v),
+        M: decimal(&message_to_field(msg)?.v),
+        msg: message_bits(msg),
+    })
+}
+
+// Same as `eddsa_input` from the 64 byte encoding of `sign` and the 32 byte
+// encoding of the public key.
+pub fn eddsa_input_from_bytes(msg: &[u8], signature: &[u8], public_key: &[u8]) -> Result<EdDSAInput, Error> {
+    if signature.len() != 64 {
+        return Err(Error::InvalidLength);
+    }
+    let signature = Sign {
+        r: BabyJubjubPoint::decode(&signature[..32])?,
+        s: BabyJubjubField::decode(&signature[32..]),
+    };
+    let public_key = BabyJubjubPoint::decode(public_key)?;
+    eddsa_input(msg, &signature, &public_key)
+}
+
+// Inputs of circomlib's EdDSAPoseidonVerifier for a signature of
+// `BabyJubjub::sign_field`, which is checked against the same equation.
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EdDSAPoseidonInput {
+    pub enabled: String,
+    pub Ax: String,
+    pub Ay: String,
+    pub R8x: String,
+    pub R8y: String,
+    pub S: String,
+    pub M: String,
+}
+
+pub fn eddsa_poseidon_input(
+    msg: &BabyJubjubField,
+    signature: &Sign<BabyJubjubField>,
+    public_key: &BabyJubjubPoint,
+) -> EdDSAPoseidonInput {
+    EdDSAPoseidonInput {
+        enabled: "1".to_string(),
+        Ax: decimal(&public_key.x.v),
+        Ay: decimal(&public_key.y.v),
+        R8x: decimal(&signature.r.x.v),
+        R8y: decimal(&signature.r.y.v),
+        S: decimal(&signature.s.v),
+        M: decimal(&msg.v),
+    }
+}
+
+// Same as `eddsa_poseidon_input` from the 32 byte encoding of the message
+// element, the 64 byte encoding of the signature and the 32 byte encoding of the
+// public key.
+pub fn eddsa_poseidon_input_from_bytes(
+    msg: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+) -> Result<EdDSAPoseidonInput, Error> {
+    if msg.len() != 32 || signature.len() != 64 {
+        return Err(Error::InvalidLength);
+    }
+    let msg = BabyJubjubField::decode(msg);
+    if &msg.v >= BabyJubjubField::order() {
+        return Err(Error::InvalidScalar);
+    }
+    let signature = Sign {
+        r: BabyJubjubPoint::decode(&signature[..32])?,
+        s: BabyJubjubField::decode(&signature[32..]),
+    };
+    let public_key = BabyJubjubPoint::decode(public_key)?;
+    Ok(eddsa_poseidon_input(&msg, &signature, &public_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::EDDSA;
+    use crate::BabyJubjub;
+
+    #[test]
+    fn test_eddsa_input() {
+        let secret_key = [1u8; 32];
+        let msg = b"\x01\x80";
+        let sig = BabyJubjub::sign(msg, &secret_key);
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+
+        let input = eddsa_input(msg, &sig, &pk).unwrap();
+        assert_eq!(input.Ax, pk.x.v.to_str_radix(10));
+        assert_eq!(input.R8y, sig.r.y.v.to_str_radix(10));
+        assert_eq!(input.S, sig.s.v.to_str_radix(10));
+        assert_eq!(input.M, "32769");
+        assert_eq!(input.msg, [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let encoded = [sig.r.encode(), sig.s.encode()].concat();
+        assert_eq!(eddsa_input_from_bytes(msg, &encoded, &pk.encode()).unwrap(), input);
+        assert_eq!(eddsa_input_from_bytes(msg, &encoded[1..], &pk.encode()), Err(Error::InvalidLength));
+
+        // M would wrap around the field
+        let long = [0xffu8; 32];
+        assert!(message_to_field(&long[..31]).is_ok());
+        assert_eq!(message_to_field(&long), Err(Error::InvalidLength));
+        let sig = BabyJubjub::sign(&long, &secret_key);
+        assert_eq!(eddsa_input(&long, &sig, &pk), Err(Error::InvalidLength));
+    }
+
+    #[test]
+    fn test_eddsa_poseidon_input() {
+        let secret_key = [3u8; 32];
+        let m = BabyJubjubField::new(&BigInt::from(1234u64));
+        let sig = BabyJubjub::sign_field(&m, &secret_key);
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+
+        let input = eddsa_poseidon_input(&m, &sig, &pk);
+        assert_eq!(input.enabled, "1");
+        assert_eq!(input.Ay, pk.y.v.to_str_radix(10));
+        assert_eq!(input.R8x, sig.r.x.v.to_str_radix(10));
+        assert_eq!(input.S, sig.s.v.to_str_radix(10));
+        assert_eq!(input.M, "1234");
+
+        let encoded = [sig.r.encode(), sig.s.encode()].concat();
+        assert_eq!(eddsa_poseidon_input_from_bytes(&m.encode(), &encoded, &pk.encode()).unwrap(), input);
+        assert_eq!(
+            eddsa_poseidon_input_from_bytes(&m.encode()[1..], &encoded, &pk.encode()),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(
+            eddsa_poseidon_input_from_bytes(&[0xff; 32], &encoded, &pk.encode()),
+            Err(Error::InvalidScalar)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json() {
+        let secret_key = [2u8; 32];
+        let sig = BabyJubjub::sign(b"m", &secret_key);
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let input = eddsa_input(b"m", &sig, &pk).unwrap();
+
+        let json: serde_json::Value = serde_json::to_value(&input).unwrap();
+        assert_eq!(json["Ax"], serde_json::Value::String(input.Ax.clone()));
+        assert_eq!(json["M"], "109");
+        assert_eq!(json["msg"], serde_json::json!([1, 0, 1, 1, 0, 1, 1, 0]));
+        assert_eq!(serde_json::from_value::<EdDSAInput>(json).unwrap(), input);
+    }
+}
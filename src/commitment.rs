@@ -0,0 +1,284 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::prime_field::{modulus, random_range, Encode, Error, Order, PrimeField, BN_0};
+use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use sp_std::ops::{Add, Sub};
+use sp_std::vec::Vec;
+
+// Pedersen commitments C = v * G + r * H, with G = BASEPOINT and H derived from
+// the encoding of BASEPOINT by hashing, so nobody knows log_G(H).
+
+const H_DOMAIN: &[u8] = b"delphinus-crypto/pedersen/H";
+const G_DOMAIN: &[u8] = b"delphinus-crypto/pedersen/G";
+
+lazy_static! {
+    static ref H: BabyJubjubPoint =
+        hash_to_point(&[H_DOMAIN, &BabyJubjubPoint::get_basepoint().encode()].concat());
+}
+
+// Try-and-increment: SHA-256(seed || counter) is read as a compressed point
+// until it decodes, then the cofactor is cleared.
+fn hash_to_point(seed: &[u8]) -> BabyJubjubPoint {
+    let mut counter = 0u32;
+    loop {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(counter.to_le_bytes());
+        let h: [u8; 32] = hasher.finalize().into();
+        counter += 1;
+
+        // only accept canonical encodings, `decode` does not reduce y
+        let p = match BabyJubjubPoint::decode(&h) {
+            Ok(p) if &p.y.v < BabyJubjubField::order() && p.encode() == h => p,
+            _ => continue,
+        };
+
+        let p = p * BigInt::from(8u8);
+        if &p != BabyJubjubPoint::get_origin() {
+            return p;
+        }
+    }
+}
+
+pub fn generator_h() -> &'static BabyJubjubPoint {
+    &H
+}
+
+// Independent generators G_0, ..., G_{n-1} for vector commitments.
+pub fn generators(n: usize) -> Vec<BabyJubjubPoint> {
+    let base = BabyJubjubPoint::get_basepoint().encode();
+    (0..n as u32)
+        .map(|i| hash_to_point(&[G_DOMAIN, &base, &i.to_le_bytes()].concat()))
+        .collect()
+}
+
+fn scalar(v: &BigInt) -> BigInt {
+    modulus(v, BabyJubjubField::suborder())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commitment {
+    pub point: BabyJubjubPoint,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Opening {
+    pub value: BigInt,
+    pub blinding: BigInt,
+}
+
+impl Opening {
+    pub fn new(value: &BigInt, blinding: &BigInt) -> Self {
+        Opening {
+            value: scalar(value),
+            blinding: scalar(blinding),
+        }
+    }
+
+    pub fn random_with_rng<R: RngCore + CryptoRng + ?Sized>(value: &BigInt, rng: &mut R) -> Self {
+        let blinding = random_range(rng, &BN_0, BabyJubjubField::suborder());
+        Self::new(value, &blinding)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn random(value: &BigInt) -> Self {
+        Self::random_with_rng(value, &mut rand::thread_rng())
+    }
+
+    pub fn encode(&self) -> [u8; 64] {
+        let mut encode = [0u8; 64];
+        encode[..32].copy_from_slice(&BabyJubjubField::new(&self.value).encode());
+        encode[32..].copy_from_slice(&BabyJubjubField::new(&self.blinding).encode());
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 64 {
+            return Err(Error::InvalidLength);
+        }
+        let value = BabyJubjubField::decode(&encode[..32]).v;
+        let blinding = BabyJubjubField::decode(&encode[32..]).v;
+        Ok(Self::new(&value, &blinding))
+    }
+}
+
+pub fn commit(value: &BigInt, blinding: &BigInt) -> Commitment {
+    Commitment {
+        point: BabyJubjubPoint::get_basepoint() * scalar(value) + generator_h() * scalar(blinding),
+    }
+}
+
+// sum(values[i] * generators[i]) + blinding * H
+pub fn commit_vector(
+    values: &[BigInt],
+    generators: &[BabyJubjubPoint],
+    blinding: &BigInt,
+) -> Result<Commitment, Error> {
+    if values.len() != generators.len() {
+        return Err(Error::InvalidLength);
+    }
+
+    let point = values
+        .iter()
+        .zip(generators.iter())
+        .fold(generator_h() * scalar(blinding), |acc, (v, g)| acc + g * scalar(v));
+    Ok(Commitment { point })
+}
+
+impl Commitment {
+    pub fn verify(&self, opening: &Opening) -> bool {
+        *self == commit(&opening.value, &opening.blinding)
+    }
+
+    pub fn verify_vector(
+        &self,
+        values: &[BigInt],
+        generators: &[BabyJubjubPoint],
+        blinding: &BigInt,
+    ) -> bool {
+        match commit_vector(values, generators, blinding) {
+            Ok(c) => *self == c,
+            Err(_) => false,
+        }
+    }
+
+    pub fn encode(&self) -> [u8; 32] {
+        self.point.encode()
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 32 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Commitment {
+            point: BabyJubjubPoint::decode(encode)?,
+        })
+    }
+}
+
+impl<'a, 'b> Add<&'b Commitment> for &'a Commitment {
+    type Output = Commitment;
+
+    fn add(self, other: &Commitment) -> Commitment {
+        Commitment {
+            point: &self.point + &other.point,
+        }
+    }
+}
+
+impl<'a, 'b> Sub<&'b Commitment> for &'a Commitment {
+    type Output = Commitment;
+
+    fn sub(self, other: &Commitment) -> Commitment {
+        Commitment {
+            point: &self.point - &other.point,
+        }
+    }
+}
+
+impl<'a, 'b> Add<&'b Opening> for &'a Opening {
+    type Output = Opening;
+
+    fn add(self, other: &Opening) -> Opening {
+        Opening::new(&(&self.value + &other.value), &(&self.blinding + &other.blinding))
+    }
+}
+
+impl<'a, 'b> Sub<&'b Opening> for &'a Opening {
+    type Output = Opening;
+
+    fn sub(self, other: &Opening) -> Opening {
+        Opening::new(&(&self.value - &other.value), &(&self.blinding - &other.blinding))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generator_h() {
+        let h = generator_h();
+        // existing commitments depend on H, it must never change
+        assert_eq!(
+            h.x.v,
+            BigInt::parse_bytes(b"119213836798487491454414475897598976803467396957117011057822754432679228360", 10).unwrap()
+        );
+        assert_eq!(
+            h.y.v,
+            BigInt::parse_bytes(b"10224458165520635316544488020139119974634068384721635707347605968199589487626", 10).unwrap()
+        );
+        assert_ne!(h, BabyJubjubPoint::get_basepoint());
+        assert_eq!(h * BabyJubjubField::suborder(), *BabyJubjubPoint::get_origin());
+
+        let g = generators(3);
+        assert_eq!(g.len(), 3);
+        assert_ne!(g[0], g[1]);
+        assert_ne!(&g[0], h);
+        assert_eq!(&g[2] * BabyJubjubField::suborder(), *BabyJubjubPoint::get_origin());
+    }
+
+    #[test]
+    fn test_commit_verify() {
+        let opening = Opening::new(&BigInt::from(100u64), &BigInt::from(12345u64));
+        let c = commit(&opening.value, &opening.blinding);
+        assert!(c.verify(&opening));
+        assert!(!c.verify(&Opening::new(&BigInt::from(101u64), &BigInt::from(12345u64))));
+        assert!(!c.verify(&Opening::new(&BigInt::from(100u64), &BigInt::from(12346u64))));
+    }
+
+    #[test]
+    fn test_homomorphic() {
+        let a = Opening::new(&BigInt::from(70u64), &BigInt::from(11u64));
+        let b = Opening::new(&BigInt::from(30u64), &BigInt::from(22u64));
+        let ca = commit(&a.value, &a.blinding);
+        let cb = commit(&b.value, &b.blinding);
+
+        let sum = &ca + &cb;
+        assert!(sum.verify(&(&a + &b)));
+        assert!(sum.verify(&Opening::new(&BigInt::from(100u64), &BigInt::from(33u64))));
+
+        // 30 - 70 wraps around the subgroup order
+        let diff = &cb - &ca;
+        assert!(diff.verify(&(&b - &a)));
+        assert!(diff.verify(&Opening::new(&BigInt::from(-40), &BigInt::from(11u64))));
+    }
+
+    #[test]
+    fn test_vector_commitment() {
+        let g = generators(3);
+        let values = [BigInt::from(1u64), BigInt::from(2u64), BigInt::from(3u64)];
+        let r = BigInt::from(99u64);
+
+        let c = commit_vector(&values, &g, &r).unwrap();
+        assert!(c.verify_vector(&values, &g, &r));
+        assert!(!c.verify_vector(&values[..2], &g, &r));
+        assert!(!c.verify_vector(&[BigInt::from(1u64), BigInt::from(3u64), BigInt::from(2u64)], &g, &r));
+        assert_eq!(commit_vector(&values[..2], &g, &r), Err(Error::InvalidLength));
+    }
+
+    #[test]
+    fn test_encode() {
+        let opening = Opening::new(&BigInt::from(5u64), &BigInt::from(6u64));
+        let c = commit(&opening.value, &opening.blinding);
+        assert_eq!(Commitment::decode(&c.encode()).unwrap(), c);
+        assert_eq!(Opening::decode(&opening.encode()).unwrap(), opening);
+        assert_eq!(Commitment::decode(&c.encode()[..31]), Err(Error::InvalidLength));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_random_opening() {
+        let opening = Opening::random(&BigInt::from(42u64));
+        let c = commit(&opening.value, &opening.blinding);
+        assert!(c.verify(&opening));
+
+        use rand::{rngs::StdRng, SeedableRng};
+        let opening = Opening::random_with_rng(&BigInt::from(42u64), &mut StdRng::seed_from_u64(1));
+        assert_eq!(opening, Opening::random_with_rng(&BigInt::from(42u64), &mut StdRng::seed_from_u64(1)));
+        assert!(commit(&opening.value, &opening.blinding).verify(&opening));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::babyjubjub::{random_scalar, BabyJubjubField};
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::key::EDDSA;
+use crate::prime_field::{Error, Order};
+use crate::BabyJubjub;
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+use sha2::Sha256;
+use sp_std::vec::Vec;
+
+// Ciphertext layout: ephemeral public key (32) || encrypted payload || tag (16).
+// The AEAD key is fresh for every message, so the nonce is fixed to zero.
+pub const EPHEMERAL_SIZE: usize = 32;
+pub const TAG_SIZE: usize = 16;
+
+const HKDF_INFO: &[u8] = b"delphinus-crypto/ecies/chacha20poly1305";
+
+fn derive_key(
+    shared: &BabyJubjubPoint,
+    ephemeral: &BabyJubjubPoint,
+    public_key: &BabyJubjubPoint,
+) -> Result<[u8; 32], Error> {
+    if shared == BabyJubjubPoint::get_origin() {
+        return Err(Error::InvalidPoint);
+    }
+
+    let salt = [ephemeral.encode(), public_key.encode()].concat();
+    let hk = Hkdf::<Sha256>::new(Some(&salt), &shared.encode());
+    let mut key = [0u8; 32];
+    hk.expand(HKDF_INFO, &mut key)
+        .map_err(|_| Error::InvalidLength)?;
+    Ok(key)
+}
+
+// The AEAD nonce is always zero, so encrypting twice with the same ephemeral
+// secret reuses the key and nonce and leaks the plaintexts. Only for tests and
+// `encrypt_to_with_rng`, which draws a fresh one every time.
+pub(crate) fn encrypt_to_with_ephemeral(
+    public_key: &BabyJubjubPoint,
+    plaintext: &[u8],
+    aad: &[u8],
+    ephemeral_secret: &BigInt,
+) -> Result<Vec<u8>, Error> {
+    let r = ephemeral_secret % BabyJubjubField::suborder();
+    let ephemeral = BabyJubjubPoint::get_basepoint() * &r;
+    let shared = public_key * &r;
+    let key = derive_key(&shared, &ephemeral, public_key)?;
+
+    let cipher = ChaCha20Poly1305::new(&Key::from(key));
+    let payload = Payload { msg: plaintext, aad };
+    let sealed = cipher
+        .encrypt(&Nonce::default(), payload)
+        .map_err(|_| Error::InvalidLength)?;
+
+    Ok([&ephemeral.encode()[..], &sealed].concat())
+}
+
+pub fn encrypt_to_with_rng<R: RngCore + CryptoRng + ?Sized>(
+    public_key: &BabyJubjubPoint,
+    plaintext: &[u8],
+    aad: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>, Error> {
+    encrypt_to_with_ephemeral(public_key, plaintext, aad, &random_scalar(rng).v)
+}
+
+#[cfg(feature = "std")]
+pub fn encrypt_to(
+    public_key: &BabyJubjubPoint,
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, Error> {
+    encrypt_to_with_rng(public_key, plaintext, aad, &mut rand::thread_rng())
+}
+
+// `secret_key` is the same key material accepted by `BabyJubjub::sign`.
+pub fn decrypt(secret_key: &[u8], ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+    if ciphertext.len() < EPHEMERAL_SIZE + TAG_SIZE {
+        return Err(Error::InvalidLength);
+    }
+
+    let ephemeral = BabyJubjubPoint::decode(&ciphertext[..EPHEMERAL_SIZE])?;
+    // The secret scalar is not a multiple of the cofactor, an ephemeral off
+    // the prime order subgroup would reveal it modulo 8.
+    if &(&ephemeral * BabyJubjubField::suborder()) != BabyJubjubPoint::get_origin() {
+        return Err(Error::InvalidPoint);
+    }
+    let s = BabyJubjub::secret_scalar(secret_key);
+    let public_key = BabyJubjubPoint::get_basepoint() * &s;
+    let shared = &ephemeral * &s;
+    let key = derive_key(&shared, &ephemeral, &public_key)?;
+
+    let cipher = ChaCha20Poly1305::new(&Key::from(key));
+    let payload = Payload {
+        msg: &ciphertext[EPHEMERAL_SIZE..],
+        aad,
+    };
+    cipher
+        .decrypt(&Nonce::default(), payload)
+        .map_err(|_| Error::DecryptionFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let secret_key = [7u8; 32];
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let msg = b"transfer 100 to alice";
+
+        let c = encrypt_to_with_ephemeral(&pk, msg, b"aad", &BigInt::from(42u64)).unwrap();
+        assert_eq!(c.len(), EPHEMERAL_SIZE + msg.len() + TAG_SIZE);
+        assert_eq!(decrypt(&secret_key, &c, b"aad").unwrap(), msg.to_vec());
+
+        let empty = encrypt_to_with_ephemeral(&pk, b"", b"", &BigInt::from(43u64)).unwrap();
+        assert_eq!(decrypt(&secret_key, &empty, b"").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_decrypt_failure() {
+        let secret_key = [7u8; 32];
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let c = encrypt_to_with_ephemeral(&pk, b"hello", b"aad", &BigInt::from(42u64)).unwrap();
+
+        assert_eq!(decrypt(&secret_key, &c, b"other"), Err(Error::DecryptionFailed));
+        assert_eq!(decrypt(&[8u8; 32], &c, b"aad"), Err(Error::DecryptionFailed));
+
+        let mut tampered = c.clone();
+        tampered[EPHEMERAL_SIZE] ^= 1;
+        assert_eq!(decrypt(&secret_key, &tampered, b"aad"), Err(Error::DecryptionFailed));
+
+        assert_eq!(decrypt(&secret_key, &c[..40], b"aad"), Err(Error::InvalidLength));
+
+        // shifted by the point of order 2
+        let torsion = BabyJubjubPoint {
+            x: BabyJubjubField { v: BigInt::from(0u64) },
+            y: BabyJubjubField { v: BabyJubjubField::order() - 1 },
+        };
+        let ephemeral = BabyJubjubPoint::decode(&c[..EPHEMERAL_SIZE]).unwrap() + torsion;
+        let shifted = [&ephemeral.encode()[..], &c[EPHEMERAL_SIZE..]].concat();
+        assert_eq!(decrypt(&secret_key, &shifted, b"aad"), Err(Error::InvalidPoint));
+
+        let origin = BabyJubjubPoint::get_origin();
+        assert_eq!(
+            encrypt_to_with_ephemeral(origin, b"hello", b"", &BigInt::from(42u64)),
+            Err(Error::InvalidPoint)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encrypt_to() {
+        let secret_key = [9u8; 32];
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+
+        let c1 = encrypt_to(&pk, b"payload", b"").unwrap();
+        let c2 = encrypt_to(&pk, b"payload", b"").unwrap();
+        assert_ne!(c1, c2);
+        assert_eq!(decrypt(&secret_key, &c1, b"").unwrap(), b"payload".to_vec());
+        assert_eq!(decrypt(&secret_key, &c2, b"").unwrap(), b"payload".to_vec());
+
+        use rand::{rngs::StdRng, SeedableRng};
+        let c3 = encrypt_to_with_rng(&pk, b"payload", b"", &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(c3, encrypt_to_with_rng(&pk, b"payload", b"", &mut StdRng::seed_from_u64(1)).unwrap());
+        assert_eq!(decrypt(&secret_key, &c3, b"").unwrap(), b"payload".to_vec());
+    }
+}
@@ -0,0 +1,252 @@
+use crate::babyjubjub::{random_scalar, BabyJubjubField};
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::prime_field::{Error, Order};
+use num_bigint::BigInt;
+use num_integer::Roots;
+use rand_core::{CryptoRng, RngCore};
+use sp_std::collections::btree_map::BTreeMap;
+use sp_std::ops::Add;
+
+// ElGamal over BabyJubjub. Keys are the same as the EdDSA ones: the secret is
+// `BabyJubjub::secret_scalar(secret_key)` and the public key is
+// `BabyJubjub::pubkey_from_secretkey(secret_key)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ciphertext {
+    pub c1: BabyJubjubPoint,
+    pub c2: BabyJubjubPoint,
+}
+
+pub fn public_key(secret: &BigInt) -> BabyJubjubPoint {
+    BabyJubjubPoint::get_basepoint() * (secret % BabyJubjubField::suborder())
+}
+
+pub fn keygen_with_rng<R: RngCore + CryptoRng + ?Sized>(rng: &mut R) -> (BigInt, BabyJubjubPoint) {
+    let secret = random_scalar(rng).v;
+    let pk = public_key(&secret);
+    (secret, pk)
+}
+
+pub fn random_nonce_with_rng<R: RngCore + CryptoRng + ?Sized>(rng: &mut R) -> BigInt {
+    random_scalar(rng).v
+}
+
+#[cfg(feature = "std")]
+pub fn keygen() -> (BigInt, BabyJubjubPoint) {
+    keygen_with_rng(&mut rand::thread_rng())
+}
+
+#[cfg(feature = "std")]
+pub fn random_nonce() -> BigInt {
+    random_nonce_with_rng(&mut rand::thread_rng())
+}
+
+// c1 = r * B, c2 = m + r * pk
+pub fn encrypt_point(
+    public_key: &BabyJubjubPoint,
+    m: &BabyJubjubPoint,
+    r: &BigInt,
+) -> Ciphertext {
+    let r = r % BabyJubjubField::suborder();
+    Ciphertext {
+        c1: BabyJubjubPoint::get_basepoint() * &r,
+        c2: m + public_key * &r,
+    }
+}
+
+pub fn decrypt_point(secret: &BigInt, ciphertext: &Ciphertext) -> BabyJubjubPoint {
+    let secret = secret % BabyJubjubField::suborder();
+    &ciphertext.c2 - &ciphertext.c1 * &secret
+}
+
+// Exponential ElGamal: the plaintext is encoded as v * B, so ciphertexts add up
+// to the encryption of the sum.
+pub fn encrypt(public_key: &BabyJubjubPoint, v: u64, r: &BigInt) -> Ciphertext {
+    let m = BabyJubjubPoint::get_basepoint() * BigInt::from(v);
+    encrypt_point(public_key, &m, r)
+}
+
+// Recovers v from an exponential ciphertext when v <= max, using baby-step
+// giant-step. Build a DecryptionTable once when decrypting many values.
+pub fn decrypt(secret: &BigInt, ciphertext: &Ciphertext, max: u64) -> Option<u64> {
+    DecryptionTable::new(max).decrypt(secret, ciphertext)
+}
+
+pub struct DecryptionTable {
+    max: u64,
+    step: u64,
+    baby_steps: BTreeMap<[u8; 32], u64>,
+    giant_step: BabyJubjubPoint,
+}
+
+impl DecryptionTable {
+    pub fn new(max: u64) -> Self {
+        let step = max.sqrt() + 1;
+        let base = BabyJubjubPoint::get_basepoint();
+
+        let mut baby_steps = BTreeMap::new();
+        let mut acc = BabyJubjubPoint::get_origin().clone();
+        for j in 0..step {
+            baby_steps.insert(acc.encode(), j);
+            acc = &acc + base;
+        }
+
+        // acc == step * B after the loop
+        DecryptionTable {
+            max,
+            step,
+            baby_steps,
+            giant_step: acc,
+        }
+    }
+
+    pub fn lookup(&self, m: &BabyJubjubPoint) -> Option<u64> {
+        let mut target = m.clone();
+        for i in 0..=self.step {
+            if let Some(j) = self.baby_steps.get(&target.encode()) {
+                // i * step + j can overflow for a max close to u64::MAX
+                let v = i.checked_mul(self.step)?.checked_add(*j)?;
+                return if v <= self.max { Some(v) } else { None };
+            }
+            target = &target - &self.giant_step;
+        }
+        None
+    }
+
+    pub fn decrypt(&self, secret: &BigInt, ciphertext: &Ciphertext) -> Option<u64> {
+        self.lookup(&decrypt_point(secret, ciphertext))
+    }
+}
+
+impl Ciphertext {
+    pub fn rerandomize(&self, public_key: &BabyJubjubPoint, r: &BigInt) -> Ciphertext {
+        self + &encrypt_point(public_key, BabyJubjubPoint::get_origin(), r)
+    }
+
+    pub fn encode(&self) -> [u8; 64] {
+        let mut encode = [0u8; 64];
+        encode[..32].copy_from_slice(&self.c1.encode());
+        encode[32..].copy_from_slice(&self.c2.encode());
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 64 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Ciphertext {
+            c1: BabyJubjubPoint::decode(&encode[..32])?,
+            c2: BabyJubjubPoint::decode(&encode[32..])?,
+        })
+    }
+}
+
+impl<'a, 'b> Add<&'b Ciphertext> for &'a Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, other: &Ciphertext) -> Ciphertext {
+        Ciphertext {
+            c1: &self.c1 + &other.c1,
+            c2: &self.c2 + &other.c2,
+        }
+    }
+}
+
+impl Add for Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, other: Ciphertext) -> Ciphertext {
+        &self + &other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BabyJubjub, EDDSA};
+
+    #[test]
+    fn test_encrypt_point() {
+        let secret = BigInt::from(123456789u64);
+        let pk = public_key(&secret);
+        let m = BabyJubjubPoint::get_basepoint() * BigInt::from(42u64);
+
+        let c = encrypt_point(&pk, &m, &BigInt::from(987654321u64));
+        assert_eq!(decrypt_point(&secret, &c), m);
+    }
+
+    #[test]
+    fn test_eddsa_keys() {
+        let secret_key = [3u8; 32];
+        let secret = BabyJubjub::secret_scalar(&secret_key);
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        assert_eq!(public_key(&secret), pk);
+
+        let c = encrypt(&pk, 7, &BigInt::from(5u64));
+        assert_eq!(decrypt(&secret, &c, 100), Some(7));
+    }
+
+    #[test]
+    fn test_homomorphic_add() {
+        let secret = BigInt::from(1111u64);
+        let pk = public_key(&secret);
+        let table = DecryptionTable::new(1000);
+
+        let a = encrypt(&pk, 300, &BigInt::from(17u64));
+        let b = encrypt(&pk, 450, &BigInt::from(19u64));
+        let sum = &a + &b;
+        assert_eq!(table.decrypt(&secret, &sum), Some(750));
+
+        let c = sum.rerandomize(&pk, &BigInt::from(23u64));
+        assert_ne!(c, sum);
+        assert_eq!(table.decrypt(&secret, &c), Some(750));
+
+        let over = encrypt(&pk, 1001, &BigInt::from(29u64));
+        assert_eq!(table.decrypt(&secret, &over), None);
+        assert_eq!(table.decrypt(&secret, &encrypt(&pk, 0, &BigInt::from(31u64))), Some(0));
+        assert_eq!(table.decrypt(&secret, &encrypt(&pk, 1000, &BigInt::from(37u64))), Some(1000));
+    }
+
+    #[test]
+    fn test_lookup_overflow() {
+        let base = BabyJubjubPoint::get_basepoint();
+        let mut baby_steps = BTreeMap::new();
+        baby_steps.insert(base.encode(), 1u64 << 63);
+        let table = DecryptionTable {
+            max: u64::MAX,
+            step: 1 << 63,
+            baby_steps,
+            giant_step: base * BigInt::from(2u64),
+        };
+        // found at i = 1, j = 2^63
+        assert_eq!(table.lookup(&(base * BigInt::from(3u64))), None);
+    }
+
+    #[test]
+    fn test_encode() {
+        let pk = public_key(&BigInt::from(99u64));
+        let c = encrypt(&pk, 12, &BigInt::from(77u64));
+        assert_eq!(Ciphertext::decode(&c.encode()).unwrap(), c);
+        assert_eq!(Ciphertext::decode(&c.encode()[..63]), Err(Error::InvalidLength));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_keygen() {
+        let (secret, pk) = keygen();
+        let c = encrypt(&pk, 5, &random_nonce());
+        assert_eq!(decrypt(&secret, &c, 10), Some(5));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_keygen_with_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let (secret, pk) = keygen_with_rng(&mut rng);
+        assert_eq!(keygen_with_rng(&mut StdRng::seed_from_u64(1)), (secret.clone(), pk.clone()));
+        let c = encrypt(&pk, 5, &random_nonce_with_rng(&mut rng));
+        assert_eq!(decrypt(&secret, &c, 10), Some(5));
+    }
+}
@@ -0,0 +1,467 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::hash_to_curve::{expand_message_xmd, hash_to_scalar};
+use crate::prime_field::{Encode, Error, Order, PrimeField, BN_0};
+use crate::schnorr::{self, ChallengeHash, PoseidonChallenge, SchnorrSignature, Sha256Challenge};
+use crate::shamir::lagrange_coefficient;
+use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+use sp_std::collections::btree_map::BTreeMap;
+use sp_std::vec::Vec;
+
+pub mod dkg;
+
+// FROST t-of-n threshold Schnorr signatures, following the two round protocol
+// of RFC 9591 over BabyJubjub. Keys are set up without a trusted dealer by the
+// Pedersen DKG in `dkg`. The aggregate is an ordinary `SchnorrSignature` under
+// the group key, checked with `schnorr::verify::<C::Challenge>`.
+//
+// 1. every participant runs `dkg::part1`, `dkg::part2` and `dkg::part3` and
+//    ends up with a `KeyPackage`, all of them share the `PublicKeyPackage`,
+// 2. round one: each signer runs `commit` and sends its `SigningCommitments`
+//    to the coordinator,
+// 3. round two: the coordinator sends the `SigningPackage` to the signers, who
+//    answer with `sign`,
+// 4. the coordinator checks the shares and combines them with `aggregate`.
+
+// Participant identifiers are the nonzero scalars 1, 2, ..., max_signers.
+pub type Identifier = u16;
+
+pub type Message<C> = <<C as Ciphersuite>::Challenge as ChallengeHash>::Message;
+
+// RFC 9591 section 6: the group is the prime order subgroup of BabyJubjub
+// generated by BASEPOINT, H2 is the Schnorr challenge of `Challenge` so that
+// aggregated signatures verify with `schnorr::verify`, and the other hashes are
+// expand_message_xmd with SHA-256 under contextString || tag.
+pub trait Ciphersuite {
+    const CONTEXT_STRING: &'static [u8];
+
+    type Challenge: ChallengeHash;
+
+    fn h1(m: &[u8]) -> BigInt {
+        hash_to_scalar(m, &[Self::CONTEXT_STRING, b"rho"].concat())
+    }
+
+    fn h2(r: &BabyJubjubPoint, verifying_key: &BabyJubjubPoint, msg: &Message<Self>) -> BigInt {
+        Self::Challenge::challenge(r, verifying_key, msg)
+    }
+
+    fn h3(m: &[u8]) -> BigInt {
+        hash_to_scalar(m, &[Self::CONTEXT_STRING, b"nonce"].concat())
+    }
+
+    fn h4(m: &[u8]) -> Vec<u8> {
+        expand_message_xmd(m, &[Self::CONTEXT_STRING, b"msg"].concat(), 32).unwrap()
+    }
+
+    fn h5(m: &[u8]) -> Vec<u8> {
+        expand_message_xmd(m, &[Self::CONTEXT_STRING, b"com"].concat(), 32).unwrap()
+    }
+
+    // proof of knowledge challenge of the DKG
+    fn hdkg(m: &[u8]) -> BigInt {
+        hash_to_scalar(m, &[Self::CONTEXT_STRING, b"dkg"].concat())
+    }
+}
+
+// Byte messages, challenge SHA-256 as in `schnorr::Sha256Challenge`.
+pub struct BabyJubjubSha256;
+
+// Field element messages, challenge Poseidon as in `schnorr::PoseidonChallenge`,
+// so that aggregated signatures can be checked in circuits.
+pub struct BabyJubjubPoseidon;
+
+impl Ciphersuite for BabyJubjubSha256 {
+    const CONTEXT_STRING: &'static [u8] = b"FROST-BABYJUBJUB-SHA256-v1";
+    type Challenge = Sha256Challenge;
+}
+
+impl Ciphersuite for BabyJubjubPoseidon {
+    const CONTEXT_STRING: &'static [u8] = b"FROST-BABYJUBJUB-POSEIDON-v1";
+    type Challenge = PoseidonChallenge;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyPackage {
+    pub identifier: Identifier,
+    pub signing_share: BigInt,
+    pub verifying_share: BabyJubjubPoint,
+    pub verifying_key: BabyJubjubPoint,
+    pub min_signers: u16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKeyPackage {
+    pub verifying_shares: BTreeMap<Identifier, BabyJubjubPoint>,
+    pub verifying_key: BabyJubjubPoint,
+}
+
+// Deliberately neither Clone nor Copy: nonces must be used for a single `sign`,
+// which consumes them.
+pub struct SigningNonces {
+    hiding: BigInt,
+    binding: BigInt,
+    commitments: SigningCommitments,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SigningCommitments {
+    pub hiding: BabyJubjubPoint,
+    pub binding: BabyJubjubPoint,
+}
+
+impl SigningCommitments {
+    pub fn encode(&self) -> [u8; 64] {
+        let mut encode = [0u8; 64];
+        encode[..32].copy_from_slice(&self.hiding.encode());
+        encode[32..].copy_from_slice(&self.binding.encode());
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 64 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(SigningCommitments {
+            hiding: BabyJubjubPoint::decode(&encode[..32])?,
+            binding: BabyJubjubPoint::decode(&encode[32..])?,
+        })
+    }
+}
+
+pub struct SigningPackage<'a, C: Ciphersuite> {
+    pub commitments: BTreeMap<Identifier, SigningCommitments>,
+    pub message: &'a Message<C>,
+}
+
+fn encode_scalar(s: &BigInt) -> [u8; 32] {
+    BabyJubjubField::new(s).encode()
+}
+
+// RFC 9591 section 4.1: nonce_generate(secret) = H3(random_bytes || secret)
+fn nonce_generate<C: Ciphersuite>(random: &[u8; 32], secret: &BigInt) -> BigInt {
+    C::h3(&[&random[..], &encode_scalar(secret)].concat())
+}
+
+// `hiding_random` and `binding_random` must be fresh uniformly random bytes.
+pub fn commit_with<C: Ciphersuite>(
+    hiding_random: &[u8; 32],
+    binding_random: &[u8; 32],
+    key_package: &KeyPackage,
+) -> (SigningNonces, SigningCommitments) {
+    let hiding = nonce_generate::<C>(hiding_random, &key_package.signing_share);
+    let binding = nonce_generate::<C>(binding_random, &key_package.signing_share);
+    let commitments = SigningCommitments {
+        hiding: BabyJubjubPoint::get_basepoint() * &hiding,
+        binding: BabyJubjubPoint::get_basepoint() * &binding,
+    };
+    let nonces = SigningNonces {
+        hiding,
+        binding,
+        commitments: commitments.clone(),
+    };
+    (nonces, commitments)
+}
+
+pub fn commit_with_rng<C: Ciphersuite, R: RngCore + CryptoRng + ?Sized>(
+    key_package: &KeyPackage,
+    rng: &mut R,
+) -> (SigningNonces, SigningCommitments) {
+    let mut hiding_random = [0u8; 32];
+    let mut binding_random = [0u8; 32];
+    rng.fill_bytes(&mut hiding_random);
+    rng.fill_bytes(&mut binding_random);
+    commit_with::<C>(&hiding_random, &binding_random, key_package)
+}
+
+#[cfg(feature = "std")]
+pub fn commit<C: Ciphersuite>(key_package: &KeyPackage) -> (SigningNonces, SigningCommitments) {
+    commit_with_rng::<C, _>(key_package, &mut rand::thread_rng())
+}
+
+fn encode_group_commitment_list(commitments: &BTreeMap<Identifier, SigningCommitments>) -> Vec<u8> {
+    commitments
+        .iter()
+        .flat_map(|(id, c)| [&encode_scalar(&BigInt::from(*id))[..], &c.encode()].concat())
+        .collect()
+}
+
+fn compute_binding_factors<C: Ciphersuite>(
+    verifying_key: &BabyJubjubPoint,
+    package: &SigningPackage<C>,
+) -> BTreeMap<Identifier, BigInt> {
+    let msg_hash = C::h4(&C::Challenge::message_bytes(package.message));
+    let commitment_hash = C::h5(&encode_group_commitment_list(&package.commitments));
+    let prefix = [&verifying_key.encode()[..], &msg_hash, &commitment_hash].concat();
+
+    package
+        .commitments
+        .keys()
+        .map(|id| {
+            let input = [&prefix[..], &encode_scalar(&BigInt::from(*id))].concat();
+            (*id, C::h1(&input))
+        })
+        .collect()
+}
+
+fn compute_group_commitment(
+    commitments: &BTreeMap<Identifier, SigningCommitments>,
+    binding_factors: &BTreeMap<Identifier, BigInt>,
+) -> BabyJubjubPoint {
+    commitments
+        .iter()
+        .fold(BabyJubjubPoint::get_origin().clone(), |acc, (id, c)| {
+            acc + &c.hiding + &c.binding * &binding_factors[id]
+        })
+}
+
+struct SigningContext {
+    binding_factors: BTreeMap<Identifier, BigInt>,
+    group_commitment: BabyJubjubPoint,
+    challenge: BigInt,
+    identifiers: Vec<Identifier>,
+}
+
+impl SigningContext {
+    fn new<C: Ciphersuite>(verifying_key: &BabyJubjubPoint, package: &SigningPackage<C>) -> Self {
+        let binding_factors = compute_binding_factors(verifying_key, package);
+        let group_commitment = compute_group_commitment(&package.commitments, &binding_factors);
+        let challenge = C::h2(&group_commitment, verifying_key, package.message);
+        SigningContext {
+            binding_factors,
+            group_commitment,
+            challenge,
+            identifiers: package.commitments.keys().cloned().collect(),
+        }
+    }
+}
+
+// z_i = d_i + e_i * rho_i + lambda_i * s_i * c
+pub fn sign<C: Ciphersuite>(
+    package: &SigningPackage<C>,
+    nonces: SigningNonces,
+    key_package: &KeyPackage,
+) -> Result<BigInt, Error> {
+    if package.commitments.len() < key_package.min_signers as usize {
+        return Err(Error::InvalidLength);
+    }
+    match package.commitments.get(&key_package.identifier) {
+        Some(c) if *c == nonces.commitments => (),
+        _ => return Err(Error::InvalidNonce),
+    }
+
+    let l = BabyJubjubField::suborder();
+    let ctx = SigningContext::new(&key_package.verifying_key, package);
+    let rho = &ctx.binding_factors[&key_package.identifier];
+    let lambda = lagrange_coefficient(key_package.identifier, &ctx.identifiers);
+
+    let z = nonces.hiding + nonces.binding * rho + lambda * &key_package.signing_share * ctx.challenge;
+    Ok(z % l)
+}
+
+// z_i * G == D_i + rho_i * E_i + c * lambda_i * Y_i
+pub fn verify_signature_share<C: Ciphersuite>(
+    package: &SigningPackage<C>,
+    identifier: Identifier,
+    share: &BigInt,
+    verifying_share: &BabyJubjubPoint,
+    verifying_key: &BabyJubjubPoint,
+) -> bool {
+    let commitments = match package.commitments.get(&identifier) {
+        Some(c) => c,
+        None => return false,
+    };
+    if share < &BN_0 || share >= BabyJubjubField::suborder() {
+        return false;
+    }
+
+    let ctx = SigningContext::new(verifying_key, package);
+    let rho = &ctx.binding_factors[&identifier];
+    let lambda = lagrange_coefficient(identifier, &ctx.identifiers);
+
+    let lhs = BabyJubjubPoint::get_basepoint() * share;
+    let rhs = &commitments.hiding + &commitments.binding * rho + verifying_share * (ctx.challenge * lambda);
+    lhs == rhs
+}
+
+// Every share is checked first, so a misbehaving signer is reported as
+// InvalidShare instead of producing an invalid signature.
+pub fn aggregate<C: Ciphersuite>(
+    package: &SigningPackage<C>,
+    shares: &BTreeMap<Identifier, BigInt>,
+    public_key_package: &PublicKeyPackage,
+) -> Result<SchnorrSignature, Error> {
+    if shares.len() != package.commitments.len() {
+        return Err(Error::InvalidLength);
+    }
+
+    let verifying_key = &public_key_package.verifying_key;
+    for (id, share) in shares.iter() {
+        let verifying_share = public_key_package
+            .verifying_shares
+            .get(id)
+            .ok_or(Error::InvalidShare)?;
+        if !verify_signature_share(package, *id, share, verifying_share, verifying_key) {
+            return Err(Error::InvalidShare);
+        }
+    }
+
+    let ctx = SigningContext::new(verifying_key, package);
+    let z = shares.values().fold(BN_0.clone(), |acc, z| acc + z);
+    let signature = SchnorrSignature {
+        r: ctx.group_commitment,
+        s: z % BabyJubjubField::suborder(),
+    };
+    debug_assert!(schnorr::verify::<C::Challenge>(package.message, &signature, verifying_key));
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // in-process run of the DKG between participants 1..=max_signers
+    fn run_dkg<C: Ciphersuite>(
+        max_signers: u16,
+        min_signers: u16,
+    ) -> (BTreeMap<Identifier, KeyPackage>, PublicKeyPackage) {
+        let mut round1_secrets = BTreeMap::new();
+        let mut round1_packages = BTreeMap::new();
+        for id in 1..=max_signers {
+            let coefficients: Vec<BigInt> = (0..min_signers)
+                .map(|k| BigInt::from(1_000_003u64 * id as u64 + 7 * k as u64 + 1))
+                .collect();
+            let (secret, package) =
+                dkg::part1_with::<C>(id, max_signers, min_signers, &coefficients, &BigInt::from(id + 99))
+                    .unwrap();
+            round1_secrets.insert(id, secret);
+            round1_packages.insert(id, package);
+        }
+
+        let others = |id: Identifier| -> BTreeMap<Identifier, dkg::Round1Package> {
+            round1_packages
+                .iter()
+                .filter(|(j, _)| **j != id)
+                .map(|(j, p)| (*j, p.clone()))
+                .collect()
+        };
+
+        let mut round2_secrets = BTreeMap::new();
+        let mut round2_shares: BTreeMap<Identifier, BTreeMap<Identifier, BigInt>> = BTreeMap::new();
+        for (id, secret) in round1_secrets.into_iter() {
+            let (secret, shares) = dkg::part2::<C>(secret, &others(id)).unwrap();
+            round2_secrets.insert(id, secret);
+            for (to, share) in shares.into_iter() {
+                round2_shares.entry(to).or_default().insert(id, share);
+            }
+        }
+
+        let mut key_packages = BTreeMap::new();
+        let mut public_key_package = None;
+        for (id, secret) in round2_secrets.iter() {
+            let (key_package, pubkey_package) =
+                dkg::part3(secret, &others(*id), &round2_shares[id]).unwrap();
+            if let Some(p) = &public_key_package {
+                assert_eq!(*p, pubkey_package);
+            }
+            public_key_package = Some(pubkey_package);
+            key_packages.insert(*id, key_package);
+        }
+        (key_packages, public_key_package.unwrap())
+    }
+
+    fn round1<C: Ciphersuite>(
+        key_packages: &BTreeMap<Identifier, KeyPackage>,
+        signers: &[Identifier],
+    ) -> (BTreeMap<Identifier, SigningNonces>, BTreeMap<Identifier, SigningCommitments>) {
+        let mut nonces = BTreeMap::new();
+        let mut commitments = BTreeMap::new();
+        for id in signers {
+            let (n, c) = commit_with::<C>(&[*id as u8; 32], &[0xff - *id as u8; 32], &key_packages[id]);
+            nonces.insert(*id, n);
+            commitments.insert(*id, c);
+        }
+        (nonces, commitments)
+    }
+
+    #[test]
+    fn test_frost_sha256() {
+        let (key_packages, pubkey_package) = run_dkg::<BabyJubjubSha256>(3, 2);
+
+        let (nonces, commitments) = round1::<BabyJubjubSha256>(&key_packages, &[1, 3]);
+        let package = SigningPackage::<BabyJubjubSha256> {
+            commitments,
+            message: b"operator batch 17",
+        };
+        let shares: BTreeMap<Identifier, BigInt> = nonces
+            .into_iter()
+            .map(|(id, n)| (id, sign(&package, n, &key_packages[&id]).unwrap()))
+            .collect();
+
+        let sig = aggregate(&package, &shares, &pubkey_package).unwrap();
+        let vk = &pubkey_package.verifying_key;
+        assert!(schnorr::verify::<Sha256Challenge>(b"operator batch 17", &sig, vk));
+        assert!(!schnorr::verify::<Sha256Challenge>(b"operator batch 18", &sig, vk));
+
+        // a corrupted share is caught before aggregation
+        let mut bad = shares.clone();
+        *bad.get_mut(&3).unwrap() += 1;
+        assert_eq!(aggregate(&package, &bad, &pubkey_package), Err(Error::InvalidShare));
+        let mut missing = shares;
+        missing.remove(&1);
+        assert_eq!(aggregate(&package, &missing, &pubkey_package), Err(Error::InvalidLength));
+    }
+
+    #[test]
+    fn test_frost_poseidon() {
+        let (key_packages, pubkey_package) = run_dkg::<BabyJubjubPoseidon>(4, 3);
+        let m = BabyJubjubField::new(&BigInt::from(424242u64));
+
+        for signers in [[1u16, 2, 3], [2, 3, 4], [1, 2, 4]].iter() {
+            let (nonces, commitments) = round1::<BabyJubjubPoseidon>(&key_packages, signers);
+            let package = SigningPackage::<BabyJubjubPoseidon> { commitments, message: &m };
+            let shares: BTreeMap<Identifier, BigInt> = nonces
+                .into_iter()
+                .map(|(id, n)| (id, sign(&package, n, &key_packages[&id]).unwrap()))
+                .collect();
+            let sig = aggregate(&package, &shares, &pubkey_package).unwrap();
+            assert!(schnorr::verify::<PoseidonChallenge>(&m, &sig, &pubkey_package.verifying_key));
+        }
+    }
+
+    #[test]
+    fn test_sign_checks() {
+        let (key_packages, _) = run_dkg::<BabyJubjubSha256>(3, 2);
+
+        // below the threshold
+        let (mut nonces, commitments) = round1::<BabyJubjubSha256>(&key_packages, &[2]);
+        let package = SigningPackage::<BabyJubjubSha256> { commitments, message: b"m" };
+        let n = nonces.remove(&2).unwrap();
+        assert_eq!(sign(&package, n, &key_packages[&2]), Err(Error::InvalidLength));
+
+        // nonces that do not match the commitments in the package
+        let (mut nonces, commitments) = round1::<BabyJubjubSha256>(&key_packages, &[1, 2]);
+        let package = SigningPackage::<BabyJubjubSha256> { commitments, message: b"m" };
+        let n = nonces.remove(&1).unwrap();
+        assert_eq!(sign(&package, n, &key_packages[&2]), Err(Error::InvalidNonce));
+
+        let c = &package.commitments[&1];
+        assert_eq!(SigningCommitments::decode(&c.encode()).unwrap(), *c);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_commit_with_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let (key_packages, _) = run_dkg::<BabyJubjubSha256>(3, 2);
+        let (_, commitments) = commit_with_rng::<BabyJubjubSha256, _>(&key_packages[&1], &mut StdRng::seed_from_u64(1));
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let (mut hiding, mut binding) = ([0u8; 32], [0u8; 32]);
+        rng.fill_bytes(&mut hiding);
+        rng.fill_bytes(&mut binding);
+        assert_eq!(commit_with::<BabyJubjubSha256>(&hiding, &binding, &key_packages[&1]).1, commitments);
+    }
+}
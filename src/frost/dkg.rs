@@ -0,0 +1,304 @@
+use super::{encode_scalar, Ciphersuite, Identifier, KeyPackage, PublicKeyPackage};
+use crate::babyjubjub::{random_scalar, BabyJubjubField};
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::prime_field::{modulus, Error, Order, BN_0};
+use crate::schnorr::SchnorrSignature;
+use crate::shamir::{evaluate_polynomial, Commitments};
+use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+use sp_std::collections::btree_map::BTreeMap;
+use sp_std::vec::Vec;
+
+// Pedersen DKG with Feldman commitments (RFC 9591 appendix C, FROST paper
+// figure 1). Each participant deals a random polynomial of degree
+// min_signers - 1, publishes commitments to its coefficients with a proof of
+// knowledge of the constant term, and privately sends f_i(j) to every j.
+// Shares are checked against the commitments, the signing share is the sum of
+// the received shares and the group key the sum of the constant terms.
+//
+// Round 2 shares must be sent over confidential and authenticated channels.
+
+pub struct Round1SecretPackage {
+    identifier: Identifier,
+    coefficients: Vec<BigInt>,
+    commitment: Commitments,
+    max_signers: u16,
+    min_signers: u16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Round1Package {
+    pub commitment: Commitments,
+    pub proof_of_knowledge: SchnorrSignature,
+}
+
+pub struct Round2SecretPackage {
+    identifier: Identifier,
+    commitment: Commitments,
+    secret_share: BigInt,
+    max_signers: u16,
+    min_signers: u16,
+}
+
+fn challenge<C: Ciphersuite>(
+    identifier: Identifier,
+    verifying_key: &BabyJubjubPoint,
+    r: &BabyJubjubPoint,
+) -> BigInt {
+    let input = [
+        &encode_scalar(&BigInt::from(identifier))[..],
+        &verifying_key.encode(),
+        &r.encode(),
+    ]
+    .concat();
+    C::hdkg(&input)
+}
+
+// `coefficients` are the min_signers coefficients of the secret polynomial and
+// `nonce` the proof of knowledge nonce, all uniformly random and secret.
+pub fn part1_with<C: Ciphersuite>(
+    identifier: Identifier,
+    max_signers: u16,
+    min_signers: u16,
+    coefficients: &[BigInt],
+    nonce: &BigInt,
+) -> Result<(Round1SecretPackage, Round1Package), Error> {
+    if min_signers < 2 || max_signers < min_signers || coefficients.len() != min_signers as usize {
+        return Err(Error::InvalidLength);
+    }
+    if identifier == 0 || identifier > max_signers {
+        return Err(Error::InvalidScalar);
+    }
+
+    let l = BabyJubjubField::suborder();
+    let coefficients: Vec<BigInt> = coefficients.iter().map(|a| modulus(a, l)).collect();
+    let commitment = Commitments(
+        coefficients
+            .iter()
+            .map(|a| BabyJubjubPoint::get_basepoint() * a)
+            .collect(),
+    );
+
+    let k = modulus(nonce, l);
+    let r = BabyJubjubPoint::get_basepoint() * &k;
+    let c = challenge::<C>(identifier, commitment.public_key(), &r);
+    let mu = (k + &coefficients[0] * c) % l;
+
+    let package = Round1Package {
+        commitment: commitment.clone(),
+        proof_of_knowledge: SchnorrSignature { r, s: mu },
+    };
+    let secret = Round1SecretPackage {
+        identifier,
+        coefficients,
+        commitment,
+        max_signers,
+        min_signers,
+    };
+    Ok((secret, package))
+}
+
+pub fn part1_with_rng<C: Ciphersuite, R: RngCore + CryptoRng + ?Sized>(
+    identifier: Identifier,
+    max_signers: u16,
+    min_signers: u16,
+    rng: &mut R,
+) -> Result<(Round1SecretPackage, Round1Package), Error> {
+    let coefficients: Vec<BigInt> = (0..min_signers).map(|_| random_scalar(rng).v).collect();
+    let nonce = random_scalar(rng).v;
+    part1_with::<C>(identifier, max_signers, min_signers, &coefficients, &nonce)
+}
+
+#[cfg(feature = "std")]
+pub fn part1<C: Ciphersuite>(
+    identifier: Identifier,
+    max_signers: u16,
+    min_signers: u16,
+) -> Result<(Round1SecretPackage, Round1Package), Error> {
+    part1_with_rng::<C, _>(identifier, max_signers, min_signers, &mut rand::thread_rng())
+}
+
+// `round1_packages` holds the packages of all other participants. Returns the
+// share to send to each of them.
+pub fn part2<C: Ciphersuite>(
+    secret: Round1SecretPackage,
+    round1_packages: &BTreeMap<Identifier, Round1Package>,
+) -> Result<(Round2SecretPackage, BTreeMap<Identifier, BigInt>), Error> {
+    if round1_packages.len() != secret.max_signers as usize - 1
+        || round1_packages.contains_key(&secret.identifier)
+    {
+        return Err(Error::InvalidLength);
+    }
+
+    for (id, package) in round1_packages.iter() {
+        if *id == 0 || *id > secret.max_signers || package.commitment.threshold() != secret.min_signers as usize {
+            return Err(Error::InvalidLength);
+        }
+
+        let proof = &package.proof_of_knowledge;
+        if proof.s < *BN_0 || &proof.s >= BabyJubjubField::suborder() {
+            return Err(Error::InvalidProof);
+        }
+        let verifying_key = package.commitment.public_key();
+        let c = challenge::<C>(*id, verifying_key, &proof.r);
+        if BabyJubjubPoint::get_basepoint() * &proof.s != &proof.r + verifying_key * c {
+            return Err(Error::InvalidProof);
+        }
+    }
+
+    let shares = round1_packages
+        .keys()
+        .map(|id| (*id, evaluate_polynomial(&secret.coefficients, *id)))
+        .collect();
+    let round2_secret = Round2SecretPackage {
+        identifier: secret.identifier,
+        secret_share: evaluate_polynomial(&secret.coefficients, secret.identifier),
+        commitment: secret.commitment,
+        max_signers: secret.max_signers,
+        min_signers: secret.min_signers,
+    };
+    Ok((round2_secret, shares))
+}
+
+// `round2_shares` holds the share received from each other participant.
+pub fn part3(
+    secret: &Round2SecretPackage,
+    round1_packages: &BTreeMap<Identifier, Round1Package>,
+    round2_shares: &BTreeMap<Identifier, BigInt>,
+) -> Result<(KeyPackage, PublicKeyPackage), Error> {
+    if round1_packages.len() != secret.max_signers as usize - 1
+        || round2_shares.len() != round1_packages.len()
+    {
+        return Err(Error::InvalidLength);
+    }
+
+    let l = BabyJubjubField::suborder();
+    let mut signing_share = secret.secret_share.clone();
+    for (id, package) in round1_packages.iter() {
+        let share = round2_shares.get(id).ok_or(Error::InvalidLength)?;
+        if share < &BN_0 || share >= l {
+            return Err(Error::InvalidShare);
+        }
+        if BabyJubjubPoint::get_basepoint() * share != package.commitment.evaluate(secret.identifier) {
+            return Err(Error::InvalidShare);
+        }
+        signing_share = (signing_share + share) % l;
+    }
+
+    // coefficient wise sum of all commitments, the commitment to the joint polynomial
+    let mut group_commitment = secret.commitment.clone();
+    for package in round1_packages.values() {
+        for (acc, c) in group_commitment.0.iter_mut().zip(package.commitment.0.iter()) {
+            *acc = &*acc + c;
+        }
+    }
+
+    let verifying_key = group_commitment.public_key().clone();
+    let verifying_shares: BTreeMap<Identifier, BabyJubjubPoint> = (1..=secret.max_signers)
+        .map(|id| (id, group_commitment.evaluate(id)))
+        .collect();
+
+    let key_package = KeyPackage {
+        identifier: secret.identifier,
+        verifying_share: verifying_shares[&secret.identifier].clone(),
+        signing_share,
+        verifying_key: verifying_key.clone(),
+        min_signers: secret.min_signers,
+    };
+    let public_key_package = PublicKeyPackage {
+        verifying_shares,
+        verifying_key,
+    };
+    Ok((key_package, public_key_package))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BabyJubjubSha256;
+    use super::*;
+    use crate::prime_field::BN_1;
+
+    fn round1(
+        max_signers: u16,
+        min_signers: u16,
+    ) -> (Vec<Round1SecretPackage>, BTreeMap<Identifier, Round1Package>) {
+        let mut secrets = Vec::new();
+        let mut packages = BTreeMap::new();
+        for id in 1..=max_signers {
+            let coefficients: Vec<BigInt> = (0..min_signers).map(|k| BigInt::from(id * 10 + k)).collect();
+            let (secret, package) =
+                part1_with::<BabyJubjubSha256>(id, max_signers, min_signers, &coefficients, &BigInt::from(id))
+                    .unwrap();
+            secrets.push(secret);
+            packages.insert(id, package);
+        }
+        (secrets, packages)
+    }
+
+    fn without(packages: &BTreeMap<Identifier, Round1Package>, id: Identifier) -> BTreeMap<Identifier, Round1Package> {
+        let mut packages = packages.clone();
+        packages.remove(&id);
+        packages
+    }
+
+    #[test]
+    fn test_invalid_proof_of_knowledge() {
+        let (mut secrets, mut packages) = round1(3, 2);
+        packages.get_mut(&2).unwrap().proof_of_knowledge.s += 1;
+        let secret = secrets.remove(0);
+        assert_eq!(
+            part2::<BabyJubjubSha256>(secret, &without(&packages, 1)).err(),
+            Some(Error::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn test_invalid_share() {
+        let (mut secrets, packages) = round1(3, 2);
+        let secret3 = secrets.pop().unwrap();
+        let secret2 = secrets.pop().unwrap();
+        let secret1 = secrets.pop().unwrap();
+
+        let (round2_secret, _) = part2::<BabyJubjubSha256>(secret1, &without(&packages, 1)).unwrap();
+        let (_, shares2) = part2::<BabyJubjubSha256>(secret2, &without(&packages, 2)).unwrap();
+        let (_, shares3) = part2::<BabyJubjubSha256>(secret3, &without(&packages, 3)).unwrap();
+
+        let mut received = BTreeMap::new();
+        received.insert(2, shares2[&1].clone());
+        received.insert(3, shares3[&1].clone());
+        let (key_package, _) = part3(&round2_secret, &without(&packages, 1), &received).unwrap();
+        assert_eq!(
+            BabyJubjubPoint::get_basepoint() * &key_package.signing_share,
+            key_package.verifying_share
+        );
+
+        received.insert(3, shares3[&2].clone());
+        assert_eq!(
+            part3(&round2_secret, &without(&packages, 1), &received).err(),
+            Some(Error::InvalidShare)
+        );
+    }
+
+    #[test]
+    fn test_parameters() {
+        let c = [BigInt::from(1u64), BigInt::from(2u64)];
+        assert!(part1_with::<BabyJubjubSha256>(1, 3, 2, &c, &BN_1).is_ok());
+        assert_eq!(part1_with::<BabyJubjubSha256>(0, 3, 2, &c, &BN_1).err(), Some(Error::InvalidScalar));
+        assert_eq!(part1_with::<BabyJubjubSha256>(4, 3, 2, &c, &BN_1).err(), Some(Error::InvalidScalar));
+        assert_eq!(part1_with::<BabyJubjubSha256>(1, 3, 3, &c, &BN_1).err(), Some(Error::InvalidLength));
+        assert_eq!(part1_with::<BabyJubjubSha256>(1, 1, 2, &c, &BN_1).err(), Some(Error::InvalidLength));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_part1_with_rng() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let (_, package) = part1_with_rng::<BabyJubjubSha256, _>(1, 3, 2, &mut StdRng::seed_from_u64(1)).unwrap();
+        let (_, again) = part1_with_rng::<BabyJubjubSha256, _>(1, 3, 2, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(package, again);
+        let (_, other) = part1::<BabyJubjubSha256>(1, 3, 2).unwrap();
+        assert_ne!(package, other);
+    }
+}
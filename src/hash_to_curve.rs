@@ -0,0 +1,284 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::montgomery::{self, MontgomeryPoint};
+use crate::prime_field::{Error, Order, PrimeField, BN_0, BN_1};
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use sha2::{Digest, Sha256};
+use sp_std::convert::TryFrom;
+use sp_std::vec::Vec;
+
+mod blake256;
+
+pub use self::blake256::blake256;
+
+// hash_to_curve for BabyJubjub in the style of RFC 9380: expand_message_xmd
+// with SHA-256, Elligator 2 on the birationally equivalent Montgomery curve
+// v^2 = u^3 + 168698 * u^2 + u, and cofactor clearing by 8.
+//
+// RFC 9380 does not define a BabyJubjub suite, the ids below follow its naming
+// scheme and are meant to be used as a prefix of the application DST.
+
+pub const SUITE_RO: &[u8] = b"BabyJubjub_XMD:SHA-256_ELL2_RO_";
+pub const SUITE_NU: &[u8] = b"BabyJubjub_XMD:SHA-256_ELL2_NU_";
+
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+// ceil((ceil(log2(p)) + k) / 8) with k = 128
+const L: usize = 48;
+
+lazy_static! {
+    static ref Z: BabyJubjubField = BabyJubjubField::new(&BigInt::from(5));
+}
+
+pub fn expand_message_xmd(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Result<Vec<u8>, Error> {
+    let ell = len_in_bytes.div_ceil(32);
+    if ell > 255 || len_in_bytes > 65535 {
+        return Err(Error::InvalidLength);
+    }
+
+    let oversize: [u8; 32];
+    let dst = if dst.len() > 255 {
+        oversize = Sha256::new().chain(OVERSIZE_DST_PREFIX).chain(dst).finalize().into();
+        &oversize[..]
+    } else {
+        dst
+    };
+    let dst_prime = [dst, &[dst.len() as u8]].concat();
+
+    let b_0 = Sha256::new()
+        .chain([0u8; 64])
+        .chain(msg)
+        .chain((len_in_bytes as u16).to_be_bytes())
+        .chain([0u8])
+        .chain(&dst_prime)
+        .finalize();
+
+    let mut uniform_bytes = Vec::with_capacity(ell * 32);
+    let mut b_i = Sha256::new().chain(b_0).chain([1u8]).chain(&dst_prime).finalize();
+    uniform_bytes.extend_from_slice(&b_i);
+    for i in 2..=ell {
+        let mut xored = [0u8; 32];
+        for (x, (a, b)) in xored.iter_mut().zip(b_0.iter().zip(b_i.iter())) {
+            *x = a ^ b;
+        }
+        b_i = Sha256::new().chain(xored).chain([i as u8]).chain(&dst_prime).finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+
+    uniform_bytes.truncate(len_in_bytes);
+    Ok(uniform_bytes)
+}
+
+pub fn hash_to_field(msg: &[u8], dst: &[u8], count: usize) -> Result<Vec<BabyJubjubField>, Error> {
+    let uniform_bytes = expand_message_xmd(msg, dst, count * L)?;
+    Ok(uniform_bytes
+        .chunks(L)
+        .map(|e| BabyJubjubField::new(&BigInt::from_bytes_be(Sign::Plus, e)))
+        .collect())
+}
+
+// Like hash_to_field, but reduced modulo the order of the prime order subgroup.
+pub fn hash_to_scalar(msg: &[u8], dst: &[u8]) -> BigInt {
+    // cannot fail, L is far below the expand_message_xmd limit
+    let uniform_bytes = expand_message_xmd(msg, dst, L).unwrap();
+    BigInt::from_bytes_be(Sign::Plus, &uniform_bytes) % BabyJubjubField::suborder()
+}
+
+fn sqrt_or_zero(a: &BabyJubjubField) -> Option<BabyJubjubField> {
+    if a.v == *BN_0 {
+        return Some(a.clone());
+    }
+    a.sqrt().ok()
+}
+
+// Elligator 2 (RFC 9380 section 6.7.1) with J = A and K = B = 1 followed by
+// the birational map onto the twisted Edwards curve. The result is on the curve
+// but not necessarily in the prime order subgroup.
+pub fn map_to_curve(u: &BabyJubjubField) -> BabyJubjubPoint {
+    let zero = BabyJubjubField::new(&BN_0);
+    let one = BabyJubjubField::new(&BN_1);
+    let j: &BabyJubjubField = &montgomery::A;
+
+    let tv = &one + &*Z * u * u;
+    let mut x1 = (&zero - j) * tv.inv();
+    if x1 == zero {
+        x1 = &zero - j;
+    }
+    let gx1 = &x1 * &x1 * &x1 + j * &x1 * &x1 + &x1;
+    let x2 = &zero - &x1 - j;
+    let gx2 = &x2 * &x2 * &x2 + j * &x2 * &x2 + &x2;
+
+    let (s, t) = match sqrt_or_zero(&gx1) {
+        Some(y) => {
+            let y = if y.v.is_odd() { y } else { &zero - y };
+            (x1, y)
+        }
+        None => {
+            // gx1 * gx2 is a square when gx1 is not
+            let y = sqrt_or_zero(&gx2).unwrap();
+            let y = if y.v.is_even() { y } else { &zero - y };
+            (x2, y)
+        }
+    };
+
+    // cannot fail, (s, t) is on the curve
+    BabyJubjubPoint::try_from(&MontgomeryPoint::Affine { u: s, v: t }).unwrap()
+}
+
+pub fn clear_cofactor(p: &BabyJubjubPoint) -> BabyJubjubPoint {
+    p * BigInt::from(8u8)
+}
+
+// Random oracle encoding: uniform in the prime order subgroup.
+pub fn hash_to_curve(msg: &[u8], dst: &[u8]) -> Result<BabyJubjubPoint, Error> {
+    let u = hash_to_field(msg, dst, 2)?;
+    let q0 = map_to_curve(&u[0]);
+    let q1 = map_to_curve(&u[1]);
+    Ok(clear_cofactor(&(q0 + q1)))
+}
+
+// Nonuniform encoding, cheaper but distinguishable from random.
+pub fn encode_to_curve(msg: &[u8], dst: &[u8]) -> Result<BabyJubjubPoint, Error> {
+    let u = hash_to_field(msg, dst, 1)?;
+    Ok(clear_cofactor(&map_to_curve(&u[0])))
+}
+
+// circomlib `getBasePoint` (pedersenHash.js): BLAKE-256 of
+// "<tag>_<index>_<try>", with both numbers zero padded to 32 digits, is read as
+// a compressed point with bit 254 cleared until it decodes, then multiplied by
+// the cofactor. `circomlib_generator(b"PedersenGenerator", i)` gives the
+// generators of circomlib's Pedersen hash.
+pub fn circomlib_generator(tag: &[u8], index: usize) -> BabyJubjubPoint {
+    let mut try_index = 0usize;
+    loop {
+        let s = format_generator_seed(tag, index, try_index);
+        let mut h = blake256(&s);
+        h[31] &= 0xBF;
+        try_index += 1;
+
+        // only accept canonical encodings
+        let p = match BabyJubjubPoint::decode(&h) {
+            Ok(p) if p.encode() == h => p,
+            _ => continue,
+        };
+
+        let p = clear_cofactor(&p);
+        if &p != BabyJubjubPoint::get_origin() {
+            return p;
+        }
+    }
+}
+
+fn format_generator_seed(tag: &[u8], index: usize, try_index: usize) -> Vec<u8> {
+    let mut s = tag.to_vec();
+    for n in [index, try_index].iter() {
+        s.push(b'_');
+        let mut digits = [b'0'; 32];
+        let mut n = *n;
+        let mut i = digits.len();
+        while n > 0 {
+            i -= 1;
+            digits[i] = b'0' + (n % 10) as u8;
+            n /= 10;
+        }
+        s.extend_from_slice(&digits);
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        let (_, v) = BigInt::parse_bytes(s.as_bytes(), 16).unwrap().to_bytes_be();
+        let mut out = sp_std::vec![0u8; s.len() / 2 - v.len()];
+        out.extend_from_slice(&v);
+        out
+    }
+
+    fn field(s: &str) -> BabyJubjubField {
+        BabyJubjubField::new(&BigInt::parse_bytes(s.as_bytes(), 10).unwrap())
+    }
+
+    fn on_curve(p: &BabyJubjubPoint) -> bool {
+        let x2 = &p.x * &p.x;
+        let y2 = &p.y * &p.y;
+        BabyJubjubPoint::get_a() * &x2 + &y2
+            == BabyJubjubField::new(&BN_1) + BabyJubjubPoint::get_d() * &x2 * &y2
+    }
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // RFC 9380 appendix K.1
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        assert_eq!(
+            expand_message_xmd(b"", dst, 0x20).unwrap(),
+            hex("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235")
+        );
+        assert_eq!(
+            expand_message_xmd(b"abc", dst, 0x20).unwrap(),
+            hex("d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615")
+        );
+        assert_eq!(expand_message_xmd(b"", dst, 0x80).unwrap().len(), 0x80);
+        assert_eq!(expand_message_xmd(b"", dst, 256 * 32), Err(Error::InvalidLength));
+    }
+
+    #[test]
+    fn test_blake256() {
+        assert_eq!(
+            blake256(b"").to_vec(),
+            hex("716f6e863f744b9ac22c97ec7b76ea5f5908bc5b2f67c61510bfc4751384ea7a")
+        );
+        assert_eq!(
+            blake256(&[0u8]).to_vec(),
+            hex("0ce8d4ef4dd7cd8d62dfded9d4edb0a774ae6a41929a74da23109e8f11139c87")
+        );
+        assert_eq!(
+            blake256(&[0u8; 72]).to_vec(),
+            hex("d419bad32d504fb7d44d460c42c5593fe544fa4c135dec31e21bd9abdcc22d41")
+        );
+    }
+
+    #[test]
+    fn test_circomlib_generator() {
+        // first base point of circomlib's pedersen.circom
+        let p = circomlib_generator(b"PedersenGenerator", 0);
+        assert_eq!(
+            p.x,
+            field("10457101036533406547632367118273992217979173478358440826365724437999023779287")
+        );
+        assert_eq!(
+            p.y,
+            field("19824078218392094440610104313265183977899662750282163392862422243483260492317")
+        );
+    }
+
+    #[test]
+    fn test_map_to_curve() {
+        for i in 0..16u64 {
+            let p = map_to_curve(&BabyJubjubField::new(&BigInt::from(i)));
+            assert!(on_curve(&p));
+        }
+    }
+
+    #[test]
+    fn test_hash_to_curve() {
+        let dst = [SUITE_RO, b"delphinus-test"].concat();
+        let p = hash_to_curve(b"abc", &dst).unwrap();
+        assert!(on_curve(&p));
+        assert_eq!(&p * BabyJubjubField::suborder(), *BabyJubjubPoint::get_origin());
+        assert_eq!(p, hash_to_curve(b"abc", &dst).unwrap());
+        assert_ne!(p, hash_to_curve(b"abd", &dst).unwrap());
+        assert_ne!(p, hash_to_curve(b"abc", b"other-dst").unwrap());
+
+        let long_dst = [b'x'; 300];
+        assert!(on_curve(&hash_to_curve(b"abc", &long_dst).unwrap()));
+
+        let dst = [SUITE_NU, b"delphinus-test"].concat();
+        let q = encode_to_curve(b"abc", &dst).unwrap();
+        assert_eq!(&q * BabyJubjubField::suborder(), *BabyJubjubPoint::get_origin());
+    }
+}
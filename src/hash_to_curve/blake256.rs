@@ -0,0 +1,110 @@
+// BLAKE-256 (the SHA-3 finalist, not BLAKE2s), needed to reproduce the
+// circomlib generator derivation, which hashes with `blake-hash("blake256")`.
+
+const IV: [u32; 8] = [
+    0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19,
+];
+
+const C: [u32; 16] = [
+    0x243F6A88, 0x85A308D3, 0x13198A2E, 0x03707344, 0xA4093822, 0x299F31D0, 0x082EFA98, 0xEC4E6C89,
+    0x452821E6, 0x38D01377, 0xBE5466CF, 0x34E90C6C, 0xC0AC29B7, 0xC97C50DD, 0x3F84D5B5, 0xB5470917,
+];
+
+const SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+const ROUNDS: usize = 14;
+
+#[allow(clippy::too_many_arguments)]
+fn g(v: &mut [u32; 16], m: &[u32; 16], s: &[usize; 16], i: usize, a: usize, b: usize, c: usize, d: usize) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(m[s[2 * i]] ^ C[s[2 * i + 1]]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(12);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(m[s[2 * i + 1]] ^ C[s[2 * i]]);
+    v[d] = (v[d] ^ v[a]).rotate_right(8);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(7);
+}
+
+// `t` is the number of message bits hashed so far, including this block
+fn compress(h: &mut [u32; 8], block: &[u8], t: u64) {
+    let mut m = [0u32; 16];
+    for (i, word) in m.iter_mut().enumerate() {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&block[4 * i..4 * i + 4]);
+        *word = u32::from_be_bytes(bytes);
+    }
+
+    let t0 = t as u32;
+    let t1 = (t >> 32) as u32;
+    let mut v = [0u32; 16];
+    v[..8].copy_from_slice(h);
+    v[8..12].copy_from_slice(&C[..4]);
+    v[12] = t0 ^ C[4];
+    v[13] = t0 ^ C[5];
+    v[14] = t1 ^ C[6];
+    v[15] = t1 ^ C[7];
+
+    for r in 0..ROUNDS {
+        let s = &SIGMA[r % 10];
+        g(&mut v, &m, s, 0, 0, 4, 8, 12);
+        g(&mut v, &m, s, 1, 1, 5, 9, 13);
+        g(&mut v, &m, s, 2, 2, 6, 10, 14);
+        g(&mut v, &m, s, 3, 3, 7, 11, 15);
+        g(&mut v, &m, s, 4, 0, 5, 10, 15);
+        g(&mut v, &m, s, 5, 1, 6, 11, 12);
+        g(&mut v, &m, s, 6, 2, 7, 8, 13);
+        g(&mut v, &m, s, 7, 3, 4, 9, 14);
+    }
+
+    for i in 0..8 {
+        h[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+pub fn blake256(data: &[u8]) -> [u8; 32] {
+    let mut h = IV;
+    let bits = (data.len() as u64) * 8;
+
+    let mut chunks = data.chunks_exact(64);
+    let mut processed = 0u64;
+    for block in &mut chunks {
+        processed += 512;
+        compress(&mut h, block, processed);
+    }
+
+    // padding: 1, zeros, 1, then the 64-bit message length
+    let rest = chunks.remainder();
+    let mut tail = [0u8; 128];
+    tail[..rest.len()].copy_from_slice(rest);
+    tail[rest.len()] = 0x80;
+    let tail_len = if rest.len() < 56 { 64 } else { 128 };
+    tail[tail_len - 9] |= 0x01;
+    tail[tail_len - 8..tail_len].copy_from_slice(&bits.to_be_bytes());
+
+    // blocks that carry no message bits are compressed with a zero counter
+    if tail_len == 64 {
+        let t = if rest.is_empty() { 0 } else { bits };
+        compress(&mut h, &tail[..64], t);
+    } else {
+        compress(&mut h, &tail[..64], bits);
+        compress(&mut h, &tail[64..], 0);
+    }
+
+    let mut out = [0u8; 32];
+    for (i, word) in h.iter().enumerate() {
+        out[4 * i..4 * i + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    out
+}
@@ -1,7 +1,11 @@
 
+use crate::babyjubjub_point::BabyJubjubPoint;
 use crate::curve::{Curve, Point};
+use crate::prime_field::Error;
 pub use crate::prime_field::{BN_0, BN_1, BN_2, PrimeField};
 use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
 
 #[derive(Debug)]
 pub struct Sign<T> {
@@ -9,6 +13,84 @@ pub struct Sign<T> {
     pub s: T,
 }
 
+// Digest signed by the prehash variants, computed by the caller so that large
+// messages can be hashed as they stream in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Prehash<F> {
+    Sha512([u8; 64]),
+    Poseidon(F),
+}
+
+impl<F> Prehash<F> {
+    pub fn sha512(data: &[u8]) -> Self {
+        let mut digest = [0u8; 64];
+        digest.copy_from_slice(&Sha512::digest(data));
+        Prehash::Sha512(digest)
+    }
+}
+
+// 32 bytes of key material for `EDDSA::sign` and the other signing functions.
+// `Debug` does not print the key.
+#[derive(Clone)]
+pub struct SecretKey([u8; 32]);
+
+impl SecretKey {
+    pub fn generate<R: RngCore + CryptoRng + ?Sized>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        SecretKey(bytes)
+    }
+
+    #[cfg(feature="std")]
+    pub fn random() -> Self {
+        Self::generate(&mut rand::thread_rng())
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        SecretKey(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(crate::BabyJubjub::pubkey_from_secretkey(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for SecretKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl sp_std::fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter<'_>) -> sp_std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKey(pub BabyJubjubPoint);
+
+impl PublicKey {
+    pub fn point(&self) -> &BabyJubjubPoint {
+        &self.0
+    }
+
+    pub fn encode(&self) -> [u8; 32] {
+        self.0.encode()
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 32 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(PublicKey(BabyJubjubPoint::decode(encode)?))
+    }
+}
+
 pub trait EDDSA<F: PrimeField, C: Curve<F>> {
     fn secret_scalar(secret_key: &[u8]) -> BigInt;
     fn pubkey_from_secretkey(secret_key: &[u8]) -> Point<F>;
@@ -23,6 +105,7 @@ pub use crate::prime_field::{Random};
 
 #[cfg(feature="std")]
 pub trait EDDSARandom<F: PrimeField + Random, C: Curve<F>> {
+    // A field element, not key material for `sign`, see `SecretKey::random`.
     fn gen_secretkey() -> F {
         F::get_random(&BN_0, F::order())
     }
@@ -2,26 +2,139 @@
 
 #[macro_use]
 extern crate lazy_static;
+extern crate alloc;
 
 use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
 use sha2::{Digest, Sha256, Sha512};
+use sp_std::vec::Vec;
 
+pub mod adaptor;
 mod babyjubjub;
 mod babyjubjub_point;
+pub mod blind;
+pub mod circom;
+pub mod commitment;
 mod curve;
+pub mod ecies;
+pub mod elgamal;
+pub mod frost;
+pub mod hash_to_curve;
 mod key;
+pub mod lsag;
+pub mod merkle;
+pub mod montgomery;
+pub mod musig2;
+pub mod note;
+pub mod poseidon;
+pub mod poseidon_encryption;
 mod prime_field;
-
-pub use crate::babyjubjub::BabyJubjubField;
+pub mod schnorr;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod shamir;
+pub mod smt;
+pub mod stream;
+pub mod typed_data;
+pub mod vrf;
+pub mod weierstrass;
+
+pub use crate::babyjubjub::{random_scalar, BabyJubjubField};
 pub use crate::babyjubjub_point::BabyJubjubPoint;
 pub use crate::curve::{Curve, Point};
-pub use crate::key::{Sign, EDDSA};
-pub use crate::prime_field::{Encode, Field, Order, PrimeField, BN_0, BN_1, BN_2};
+pub use crate::key::{Prehash, PublicKey, SecretKey, Sign, EDDSA};
+pub use crate::prime_field::{random_range, Encode, Error, Field, Order, PrimeField, Random, BN_0, BN_1, BN_2};
 
 pub trait EllipticCurve<T> {}
 
 pub type BabyJubjub = dyn EllipticCurve<BabyJubjubPoint>;
 
+const FIELD_NONCE_DOMAIN: &[u8] = b"delphinus-crypto/eddsa-poseidon/nonce";
+
+// Poseidon(R.x, R.y, A.x, A.y, m_1, ..., m_n)
+fn field_challenge(r: &BabyJubjubPoint, a: &BabyJubjubPoint, msg: &[BabyJubjubField]) -> Result<BigInt, Error> {
+    if msg.is_empty() || msg.len() > poseidon::MAX_INPUTS - 4 {
+        return Err(Error::InvalidLength);
+    }
+    let inputs = [&[r.x.clone(), r.y.clone(), a.x.clone(), a.y.clone()][..], msg].concat();
+    Ok(poseidon::hash(&inputs)?.v)
+}
+
+const DOM_PREFIX: &[u8] = b"SigBabyJubjub no BabyJubjub collisions";
+const DOM_FLAG_CTX: u8 = 0;
+const DOM_FLAG_SHA512: u8 = 1;
+const DOM_FLAG_POSEIDON: u8 = 2;
+
+fn dom(flag: u8, context: &[u8]) -> Result<Vec<u8>, Error> {
+    if context.len() > 255 {
+        return Err(Error::InvalidLength);
+    }
+    Ok([DOM_PREFIX, &[flag, context.len() as u8], context].concat())
+}
+
+fn prehash_bytes(digest: &Prehash<BabyJubjubField>) -> (u8, Vec<u8>) {
+    match digest {
+        Prehash::Sha512(d) => (DOM_FLAG_SHA512, d.to_vec()),
+        Prehash::Poseidon(d) => (DOM_FLAG_POSEIDON, d.encode().to_vec()),
+    }
+}
+
+// hash_msg(dom || R || A || data), fed piece by piece.
+fn dom_challenge(dom: &[u8], r: &BabyJubjubPoint, a: &BabyJubjubPoint, data: &[u8]) -> BigInt {
+    let mut hasher = Sha256::new();
+    hasher.update(dom);
+    hasher.update(r.encode());
+    hasher.update(a.encode());
+    hasher.update(data);
+    BigInt::from_bytes_le(num_bigint::Sign::Plus, &hasher.finalize())
+}
+
+// `sign` with `dom` prefixed to both hashes, an empty `dom` is plain `sign`.
+// `noise` goes before the nonce key in the nonce hash.
+fn sign_with_dom(dom: &[u8], noise: &[u8], data: &[u8], secret_key: &[u8]) -> Sign<BabyJubjubField> {
+    let h = BabyJubjub::hash_key(secret_key);
+    let pk = BabyJubjub::pubkey_from_secretkey(secret_key);
+
+    let mut s_bytes = [0u8; 32];
+    s_bytes[..].copy_from_slice(&h[..32]);
+    s_bytes[0] &= 0xF8;
+    s_bytes[31] &= 0x7F;
+    s_bytes[31] |= 0x40;
+
+    let s = BigInt::from_bytes_le(num_bigint::Sign::Plus, &s_bytes);
+
+    let mut hasher = Sha512::new();
+    hasher.update(dom);
+    hasher.update(noise);
+    hasher.update(&h[32..]);
+    hasher.update(data);
+    let r = BigInt::from_bytes_le(num_bigint::Sign::Plus, &hasher.finalize()) % BabyJubjubField::suborder();
+
+    let sig_r = BabyJubjubPoint::get_basepoint() * &r;
+    let concat = dom_challenge(dom, &sig_r, &pk, data);
+
+    let sig_s = BabyJubjubField::new(&((r + concat * s) % BabyJubjubField::suborder()));
+    Sign::<BabyJubjubField> { r: sig_r, s: sig_s }
+}
+
+fn verify_with_dom(dom: &[u8], data: &[u8], signature: Sign<BabyJubjubField>, public_key: BabyJubjubPoint) -> bool {
+    verify_challenge(dom_challenge(dom, &signature.r, &public_key, data), signature, public_key)
+}
+
+// `concat` is hash_msg(dom || R || A || data) as a little endian integer.
+// S + suborder satisfies the same equation, so S must be reduced for the
+// signature to be unique.
+fn verify_challenge(concat: BigInt, signature: Sign<BabyJubjubField>, public_key: BabyJubjubPoint) -> bool {
+    if &signature.s.v >= BabyJubjubField::suborder() {
+        return false;
+    }
+    let l = BabyJubjubPoint::get_basepoint() * &signature.s.v;
+    let r1 = public_key * (8 * concat);
+    let r2 = signature.r + r1;
+
+    l == r2
+}
+
 impl EDDSA<BabyJubjubField, BabyJubjubPoint> for BabyJubjub {
     fn secret_scalar(secret_key: &[u8]) -> BigInt {
         let mut h = Self::hash_key(&secret_key);
@@ -43,19 +156,53 @@ impl EDDSA<BabyJubjubField, BabyJubjubPoint> for BabyJubjub {
     }
 
     fn verify(data: &[u8], signature: Sign<BabyJubjubField>, public_key: BabyJubjubPoint) -> bool {
-        let h = Self::hash_msg(&([&signature.r.encode(), &public_key.encode(), data].concat()));
-        let concat = BigInt::from_bytes_le(num_bigint::Sign::Plus, &h);
+        verify_with_dom(&[], data, signature, public_key)
+    }
 
-        let l = BabyJubjubPoint::get_basepoint() * &signature.s.v;
-        let r1 = public_key * (8 * concat);
-        let r2 = signature.r + r1;
+    fn sign(data: &[u8], secret_key: &[u8]) -> Sign<BabyJubjubField> {
+        sign_with_dom(&[], &[], data, secret_key)
+    }
 
-        l == r2
+    fn hash_key(data: &[u8]) -> [u8; 64] {
+        let mut res = [0u8; 64];
+        let mut hasher = Sha512::new();
+        hasher.update(data);
+        res.copy_from_slice(&hasher.finalize());
+        res
     }
 
-    fn sign(data: &[u8], secret_key: &[u8]) -> Sign<BabyJubjubField> {
-        let h = Self::hash_key(secret_key);
-        let pk = Self::pubkey_from_secretkey(&secret_key);
+    fn hash_msg(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+}
+
+impl BabyJubjub {
+    // `sign` with 32 bytes from `rng` mixed into the nonce, which keeps a fault
+    // during signing from revealing the key. Verified by `verify`.
+    pub fn sign_hedged<R: RngCore + CryptoRng + ?Sized>(
+        data: &[u8],
+        secret_key: &[u8],
+        rng: &mut R,
+    ) -> Sign<BabyJubjubField> {
+        let mut noise = [0u8; 32];
+        rng.fill_bytes(&mut noise);
+        sign_with_dom(&[], &noise, data, secret_key)
+    }
+}
+
+// Field element messages, challenged with Poseidon(R.x, R.y, A.x, A.y, m_1,
+// ..., m_n) instead of hash_msg, for 1 <= n <= 12. With a single element the
+// verification equation is circomlib's EdDSA-Poseidon, so its verifier accepts
+// these signatures, see `circom::eddsa_poseidon_input`. The key and nonce
+// derivation are not: they use SHA-512 as `sign` does, where circomlib uses
+// blake512, so the same secret key gives a different public key and different
+// signatures there.
+impl BabyJubjub {
+    pub fn sign_fields(msg: &[BabyJubjubField], secret_key: &[u8]) -> Result<Sign<BabyJubjubField>, Error> {
+        let h = BabyJubjub::hash_key(secret_key);
+        let pk = BabyJubjub::pubkey_from_secretkey(secret_key);
 
         let mut s_bytes = [0u8; 32];
         s_bytes[..].copy_from_slice(&h[..32]);
@@ -65,31 +212,88 @@ impl EDDSA<BabyJubjubField, BabyJubjubPoint> for BabyJubjub {
 
         let s = BigInt::from_bytes_le(num_bigint::Sign::Plus, &s_bytes);
 
-        let r = Self::hash_key(&[&h[32..], data].concat());
+        // The domain goes first so that no byte message signed with `sign`
+        // shares the nonce of a field element message.
+        let mut nonce_input = [FIELD_NONCE_DOMAIN, &h[32..]].concat();
+        for m in msg.iter() {
+            nonce_input.extend_from_slice(&m.encode());
+        }
+        let r = BabyJubjub::hash_key(&nonce_input);
         let r = BigInt::from_bytes_le(num_bigint::Sign::Plus, &r) % BabyJubjubField::suborder();
 
         let sig_r = BabyJubjubPoint::get_basepoint() * &r;
+        let hm = field_challenge(&sig_r, &pk, msg)?;
+
+        let sig_s = BabyJubjubField::new(&((r + hm * s) % BabyJubjubField::suborder()));
+        Ok(Sign::<BabyJubjubField> { r: sig_r, s: sig_s })
+    }
 
-        let concat = [&sig_r.encode(), &pk.encode(), data].concat();
-        let hash_concat = Self::hash_msg(&concat);
-        let concat = BigInt::from_bytes_le(num_bigint::Sign::Plus, &hash_concat);
+    pub fn verify_fields(msg: &[BabyJubjubField], signature: Sign<BabyJubjubField>, public_key: BabyJubjubPoint) -> bool {
+        if &signature.s.v >= BabyJubjubField::suborder() {
+            return false;
+        }
+        let hm = match field_challenge(&signature.r, &public_key, msg) {
+            Ok(hm) => hm,
+            Err(_) => return false,
+        };
 
-        let sig_s = BabyJubjubField::new(&((r + concat * s) % BabyJubjubField::suborder()));
-        Sign::<BabyJubjubField> { r: sig_r, s: sig_s }
+        let l = BabyJubjubPoint::get_basepoint() * &signature.s.v;
+        let r1 = public_key * (8 * hm);
+        let r2 = signature.r + r1;
+
+        l == r2
     }
 
-    fn hash_key(data: &[u8]) -> [u8; 64] {
-        let mut res = [0u8; 64];
-        let mut hasher = Sha512::new();
-        hasher.update(data);
-        res.copy_from_slice(&hasher.finalize());
-        res
+    pub fn sign_field(msg: &BabyJubjubField, secret_key: &[u8]) -> Sign<BabyJubjubField> {
+        // cannot fail, there is one element
+        Self::sign_fields(sp_std::slice::from_ref(msg), secret_key).unwrap()
     }
 
-    fn hash_msg(data: &[u8]) -> [u8; 32] {
-        let mut hasher = Sha256::new();
-        hasher.update(data);
-        hasher.finalize().into()
+    pub fn verify_field(msg: &BabyJubjubField, signature: Sign<BabyJubjubField>, public_key: BabyJubjubPoint) -> bool {
+        Self::verify_fields(sp_std::slice::from_ref(msg), signature, public_key)
+    }
+}
+
+// Ed25519ctx and Ed25519ph analogues (RFC 8032 section 5.1): both hashes of
+// `sign` are prefixed with
+//
+//   "SigBabyJubjub no BabyJubjub collisions" || flag || len(context) || context
+//
+// with flag 0 for `sign_ctx`, 1 for a SHA-512 prehash and 2 for a Poseidon
+// prehash, so that a signature only verifies in the mode and context it was
+// made for. Contexts are at most 255 bytes.
+impl BabyJubjub {
+    pub fn sign_ctx(data: &[u8], context: &[u8], secret_key: &[u8]) -> Result<Sign<BabyJubjubField>, Error> {
+        Ok(sign_with_dom(&dom(DOM_FLAG_CTX, context)?, &[], data, secret_key))
+    }
+
+    pub fn verify_ctx(data: &[u8], context: &[u8], signature: Sign<BabyJubjubField>, public_key: BabyJubjubPoint) -> bool {
+        match dom(DOM_FLAG_CTX, context) {
+            Ok(dom) => verify_with_dom(&dom, data, signature, public_key),
+            Err(_) => false,
+        }
+    }
+
+    pub fn sign_prehashed(
+        digest: &Prehash<BabyJubjubField>,
+        context: &[u8],
+        secret_key: &[u8],
+    ) -> Result<Sign<BabyJubjubField>, Error> {
+        let (flag, digest) = prehash_bytes(digest);
+        Ok(sign_with_dom(&dom(flag, context)?, &[], &digest, secret_key))
+    }
+
+    pub fn verify_prehashed(
+        digest: &Prehash<BabyJubjubField>,
+        context: &[u8],
+        signature: Sign<BabyJubjubField>,
+        public_key: BabyJubjubPoint,
+    ) -> bool {
+        let (flag, digest) = prehash_bytes(digest);
+        match dom(flag, context) {
+            Ok(dom) => verify_with_dom(&dom, &digest, signature, public_key),
+            Err(_) => false,
+        }
     }
 }
 
@@ -209,6 +413,55 @@ pub fn get_public_key(secret_key: &[u8]) -> Vec<u8> {
     BabyJubjub::pubkey_from_secretkey(&secret_key).encode().into()
 }
 
+#[cfg(feature = "std")]
+#[wasm_bindgen]
+pub fn ecies_encrypt_to(public_key: &[u8], plaintext: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    let pk = BabyJubjubPoint::decode(public_key).ok()?;
+    ecies::encrypt_to(&pk, plaintext, aad).ok()
+}
+
+#[cfg(feature = "std")]
+#[wasm_bindgen]
+pub fn ecies_decrypt(secret_key: &[u8], ciphertext: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    ecies::decrypt(secret_key, ciphertext, aad).ok()
+}
+
+#[cfg(feature = "serde-wasm")]
+#[wasm_bindgen]
+pub fn generate_circom_input(msg: &[u8], signature: &[u8], public_key: &[u8]) -> Option<JsValue> {
+    let input = circom::eddsa_input_from_bytes(msg, signature, public_key).ok()?;
+    serde_wasm_bindgen::to_value(&input).ok()
+}
+
+#[cfg(feature = "serde-wasm")]
+#[wasm_bindgen]
+pub fn generate_circom_poseidon_input(msg: &[u8], signature: &[u8], public_key: &[u8]) -> Option<JsValue> {
+    let input = circom::eddsa_poseidon_input_from_bytes(msg, signature, public_key).ok()?;
+    serde_wasm_bindgen::to_value(&input).ok()
+}
+
+#[cfg(feature = "serde-wasm")]
+fn typed_data_from_js(data: JsValue) -> Option<(typed_data::Domain, typed_data::TypeSchema, Vec<typed_data::Value>)> {
+    let data: typed_data::TypedDataJson = serde_wasm_bindgen::from_value(data).ok()?;
+    data.parse().ok()
+}
+
+#[cfg(feature = "serde-wasm")]
+#[wasm_bindgen]
+pub fn typed_data_hash(data: JsValue) -> Option<Vec<u8>> {
+    let (domain, schema, values) = typed_data_from_js(data)?;
+    let hash = typed_data::hash_typed_data(&domain, &schema, &values).ok()?;
+    Some(hash.encode().to_vec())
+}
+
+#[cfg(feature = "serde-wasm")]
+#[wasm_bindgen]
+pub fn sign_typed_data(data: JsValue, secret_key: &[u8]) -> Option<Vec<u8>> {
+    let (domain, schema, values) = typed_data_from_js(data)?;
+    let sign = typed_data::sign(&domain, &schema, &values, secret_key).ok()?;
+    Some([sign.r.encode(), sign.s.encode()].concat())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -352,6 +605,141 @@ mod tests {
         assert!(verify)
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_secret_key_generate() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let key = SecretKey::generate(&mut StdRng::seed_from_u64(5));
+        assert_eq!(key.as_bytes(), SecretKey::generate(&mut StdRng::seed_from_u64(5)).as_bytes());
+        assert_ne!(key.as_bytes(), SecretKey::random().as_bytes());
+        assert_eq!(format!("{:?}", key), "SecretKey(..)");
+
+        let public_key = BabyJubjub::pubkey_from_secretkey(key.as_bytes());
+        let sign = BabyJubjub::sign(b"m", key.as_bytes());
+        assert!(BabyJubjub::verify(b"m", sign, public_key));
+    }
+
+    #[test]
+    fn test_signature_s_range() {
+        let secret_key = [2u8; 32];
+        let public_key = BabyJubjub::pubkey_from_secretkey(&secret_key);
+
+        // S + suborder satisfies the equation too
+        let sign = BabyJubjub::sign(b"m", &secret_key);
+        let s = BabyJubjubField::new(&(&sign.s.v + BabyJubjubField::suborder()));
+        assert!(!BabyJubjub::verify(b"m", Sign { r: sign.r.clone(), s: s.clone() }, public_key.clone()));
+        assert!(BabyJubjub::verify(b"m", sign, public_key));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_hedged_signature_verify() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let secret_key = [2u8; 32];
+        let public_key = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let msg = [1u8; 3];
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let sign1 = BabyJubjub::sign_hedged(&msg, &secret_key, &mut rng);
+        let sign2 = BabyJubjub::sign_hedged(&msg, &secret_key, &mut rng);
+        let sign = BabyJubjub::sign(&msg, &secret_key);
+        assert_ne!(sign1.r, sign2.r);
+        assert_ne!(sign1.r, sign.r);
+        assert!(BabyJubjub::verify(&msg, sign1, public_key.clone()));
+        assert!(BabyJubjub::verify(&msg, sign2, public_key.clone()));
+        assert!(!BabyJubjub::verify(&msg[1..], sign, public_key));
+    }
+
+    #[test]
+    fn test_field_signature_verify() {
+        let secret_key = [2u8; 32];
+        let public_key = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let m = BabyJubjubField::new(&BigInt::from(1234u64));
+
+        // S * B8 = R8 + 8 * Poseidon(R8.x, R8.y, A.x, A.y, m) * A
+        let sign = BabyJubjub::sign_field(&m, &secret_key);
+        let hm = poseidon::hash(&[
+            sign.r.x.clone(),
+            sign.r.y.clone(),
+            public_key.x.clone(),
+            public_key.y.clone(),
+            m.clone(),
+        ])
+        .unwrap();
+        assert_eq!(
+            BabyJubjubPoint::get_basepoint() * &sign.s.v,
+            &sign.r + &public_key * (8 * hm.v)
+        );
+        assert!(BabyJubjub::verify_field(&m, sign, public_key.clone()));
+
+        let other = BabyJubjubField::new(&BigInt::from(1235u64));
+        let sign = BabyJubjub::sign_field(&m, &secret_key);
+        assert!(!BabyJubjub::verify_field(&other, sign, public_key.clone()));
+
+        // the nonce is not shared with the byte message of the same encoding
+        let by_bytes = BabyJubjub::sign(&m.encode(), &secret_key);
+        assert_ne!(by_bytes.r, BabyJubjub::sign_field(&m, &secret_key).r);
+
+        let msg = [m.clone(), other.clone(), m.clone()];
+        let sign = BabyJubjub::sign_fields(&msg, &secret_key).unwrap();
+        assert!(BabyJubjub::verify_fields(&msg, sign, public_key.clone()));
+        let sign = BabyJubjub::sign_fields(&msg, &secret_key).unwrap();
+        assert!(!BabyJubjub::verify_fields(&msg[..2], sign, public_key.clone()));
+
+        assert!(BabyJubjub::sign_fields(&[], &secret_key).is_err());
+        assert!(BabyJubjub::sign_fields(&sp_std::vec![m.clone(); 13], &secret_key).is_err());
+
+        // S + suborder satisfies the equation too
+        let sign = BabyJubjub::sign_field(&m, &secret_key);
+        let s = BabyJubjubField::new(&(&sign.s.v + BabyJubjubField::suborder()));
+        assert!(!BabyJubjub::verify_field(&m, Sign { r: sign.r, s }, public_key));
+    }
+
+    #[test]
+    fn test_ctx_prehash_signature_verify() {
+        let secret_key = [3u8; 32];
+        let public_key = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let msg = b"deposit 100";
+
+        let sign = BabyJubjub::sign_ctx(msg, b"app-a", &secret_key).unwrap();
+        assert!(BabyJubjub::verify_ctx(msg, b"app-a", sign, public_key.clone()));
+        let sign = BabyJubjub::sign_ctx(msg, b"app-a", &secret_key).unwrap();
+        assert!(!BabyJubjub::verify_ctx(msg, b"app-b", sign, public_key.clone()));
+        let sign = BabyJubjub::sign_ctx(msg, b"", &secret_key).unwrap();
+        assert!(!BabyJubjub::verify(msg, sign, public_key.clone()));
+        let sign = BabyJubjub::sign(msg, &secret_key);
+        assert!(!BabyJubjub::verify_ctx(msg, b"", sign, public_key.clone()));
+        assert!(BabyJubjub::sign_ctx(msg, &[0u8; 256], &secret_key).is_err());
+
+        let digest = Prehash::sha512(msg);
+        let sign = BabyJubjub::sign_prehashed(&digest, b"app-a", &secret_key).unwrap();
+        assert!(BabyJubjub::verify_prehashed(&digest, b"app-a", sign, public_key.clone()));
+        // a prehash signature is not a signature of the digest bytes
+        let sign = BabyJubjub::sign_prehashed(&digest, b"app-a", &secret_key).unwrap();
+        let digest_bytes = match &digest {
+            Prehash::Sha512(d) => d.to_vec(),
+            _ => unreachable!(),
+        };
+        assert!(!BabyJubjub::verify_ctx(&digest_bytes, b"app-a", sign, public_key.clone()));
+
+        let digest = Prehash::Poseidon(BabyJubjubField::new(&BigInt::from(42u64)));
+        let sign = BabyJubjub::sign_prehashed(&digest, b"", &secret_key).unwrap();
+        assert!(BabyJubjub::verify_prehashed(&digest, b"", sign, public_key.clone()));
+        let sign = BabyJubjub::sign_prehashed(&digest, b"", &secret_key).unwrap();
+        let other = Prehash::Poseidon(BabyJubjubField::new(&BigInt::from(43u64)));
+        assert!(!BabyJubjub::verify_prehashed(&other, b"", sign, public_key.clone()));
+
+        // S + suborder satisfies the equation too
+        let sign = BabyJubjub::sign_ctx(msg, b"app-a", &secret_key).unwrap();
+        let s = BabyJubjubField::new(&(&sign.s.v + BabyJubjubField::suborder()));
+        assert!(!BabyJubjub::verify_ctx(msg, b"app-a", Sign { r: sign.r, s }, public_key.clone()));
+        let sign = BabyJubjub::sign_prehashed(&digest, b"", &secret_key).unwrap();
+        let s = BabyJubjubField::new(&(&sign.s.v + BabyJubjubField::suborder()));
+        assert!(!BabyJubjub::verify_prehashed(&digest, b"", Sign { r: sign.r, s }, public_key));
+    }
+
     #[test]
     fn test_decode() {
         let (_, secret_key) = BigInt::parse_bytes(
@@ -0,0 +1,316 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::hash_to_curve::{hash_to_curve, hash_to_scalar, SUITE_RO};
+use crate::prime_field::{modulus, Encode, Error, Order, PrimeField, BN_0};
+use crate::schnorr;
+use num_bigint::{BigInt, Sign};
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use sp_std::vec::Vec;
+
+// Linkable spontaneous anonymous group signatures (LSAG, Liu, Wei and Wong
+// 2004, in the form used by Monero). A signer holding x with P_pi = x * G in the
+// ring P_0, ..., P_{n-1} publishes the key image I = x * Hp(P_pi), where Hp is
+// hash_to_curve. Verification reveals nothing about pi, but two signatures by
+// the same key have the same key image, whatever the ring or the message, so
+// the key image acts as a nullifier.
+//
+// The ring and the message are hashed once into a prefix, each of the n
+// challenges then costs one short hash, signing and verification cost 4n
+// scalar multiplications and n hash_to_curve. Signatures are 32 * (n + 2)
+// bytes: c_0 || I || s_0 || ... || s_{n-1}.
+
+const HP_DST: &[u8] = b"delphinus-crypto/lsag/Hp";
+const PREFIX_DOMAIN: &[u8] = b"delphinus-crypto/lsag/prefix";
+const CHALLENGE_DST: &[u8] = b"delphinus-crypto/lsag/challenge";
+const NONCE_DST: &[u8] = b"delphinus-crypto/lsag/nonce";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyImage(pub BabyJubjubPoint);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RingSignature {
+    pub c0: BigInt,
+    pub key_image: KeyImage,
+    pub s: Vec<BigInt>,
+}
+
+fn encode_scalar(s: &BigInt) -> [u8; 32] {
+    BabyJubjubField::new(s).encode()
+}
+
+impl RingSignature {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encode = Vec::with_capacity(32 * (self.s.len() + 2));
+        encode.extend_from_slice(&encode_scalar(&self.c0));
+        encode.extend_from_slice(&self.key_image.0.encode());
+        for s in self.s.iter() {
+            encode.extend_from_slice(&encode_scalar(s));
+        }
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        let chunks = encode.chunks_exact(32);
+        if encode.len() < 96 || !chunks.remainder().is_empty() {
+            return Err(Error::InvalidLength);
+        }
+
+        let l = BabyJubjubField::suborder();
+        let scalars = chunks
+            .map(|c| BigInt::from_bytes_le(Sign::Plus, c))
+            .collect::<Vec<_>>();
+        if scalars.iter().enumerate().any(|(i, s)| i != 1 && s >= l) {
+            return Err(Error::InvalidScalar);
+        }
+
+        Ok(RingSignature {
+            c0: scalars[0].clone(),
+            key_image: KeyImage(BabyJubjubPoint::decode(&encode[32..64])?),
+            s: scalars[2..].to_vec(),
+        })
+    }
+}
+
+fn hash_point(p: &BabyJubjubPoint) -> BabyJubjubPoint {
+    let dst = [SUITE_RO, HP_DST].concat();
+    // cannot fail, the length is fixed
+    hash_to_curve(&p.encode(), &dst).unwrap()
+}
+
+pub fn key_image(secret: &BigInt) -> KeyImage {
+    let x = modulus(secret, BabyJubjubField::suborder());
+    KeyImage(hash_point(&schnorr::public_key(&x)) * x)
+}
+
+fn prefix(ring: &[BabyJubjubPoint], key_image: &KeyImage, msg: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new()
+        .chain(PREFIX_DOMAIN)
+        .chain((ring.len() as u64).to_le_bytes());
+    for p in ring.iter() {
+        hasher = hasher.chain(p.encode());
+    }
+    hasher
+        .chain(key_image.0.encode())
+        .chain((msg.len() as u64).to_le_bytes())
+        .chain(msg)
+        .finalize()
+        .into()
+}
+
+fn challenge(prefix: &[u8; 32], l: &BabyJubjubPoint, r: &BabyJubjubPoint) -> BigInt {
+    hash_to_scalar(&[&prefix[..], &l.encode(), &r.encode()].concat(), CHALLENGE_DST)
+}
+
+// Ring members and key images must be in the prime order subgroup, otherwise
+// adding a torsion point gives the same secret a second, unlinked key image.
+fn in_subgroup(p: &BabyJubjubPoint) -> bool {
+    let origin = BabyJubjubPoint::get_origin();
+    p != origin && &(p * BabyJubjubField::suborder()) == origin
+}
+
+// L_i = s_i * G + c_i * P_i, R_i = s_i * Hp(P_i) + c_i * I
+fn next_challenge(
+    prefix: &[u8; 32],
+    public_key: &BabyJubjubPoint,
+    key_image: &KeyImage,
+    c: &BigInt,
+    s: &BigInt,
+) -> BigInt {
+    let l = BabyJubjubPoint::get_basepoint() * s + public_key * c;
+    let r = hash_point(public_key) * s + &key_image.0 * c;
+    challenge(prefix, &l, &r)
+}
+
+// The nonce alpha and the fake responses s_i are derived from `seed`, the
+// secret and the message. A fresh random seed hides the signer even from
+// someone who knows the secret key.
+pub fn sign_with_seed(
+    msg: &[u8],
+    ring: &[BabyJubjubPoint],
+    secret: &BigInt,
+    seed: &[u8; 32],
+) -> Result<RingSignature, Error> {
+    if ring.is_empty() {
+        return Err(Error::InvalidLength);
+    }
+    if !ring.iter().all(in_subgroup) {
+        return Err(Error::InvalidPoint);
+    }
+
+    let n = ring.len();
+    let order = BabyJubjubField::suborder();
+    let x = modulus(secret, order);
+    let public_key = schnorr::public_key(&x);
+    let pi = ring.iter().position(|p| *p == public_key).ok_or(Error::InvalidPoint)?;
+
+    let key_image = key_image(&x);
+    let prefix = prefix(ring, &key_image, msg);
+    let nonce = |i: usize| {
+        let input = [&seed[..], &encode_scalar(&x), &prefix, &(i as u64).to_le_bytes()].concat();
+        hash_to_scalar(&input, NONCE_DST)
+    };
+
+    let alpha = nonce(n);
+    let mut c = sp_std::vec![BN_0.clone(); n];
+    let mut s: Vec<BigInt> = (0..n).map(nonce).collect();
+
+    let l = BabyJubjubPoint::get_basepoint() * &alpha;
+    let r = hash_point(&public_key) * &alpha;
+    c[(pi + 1) % n] = challenge(&prefix, &l, &r);
+    for k in 1..n {
+        let i = (pi + k) % n;
+        c[(i + 1) % n] = next_challenge(&prefix, &ring[i], &key_image, &c[i], &s[i]);
+    }
+    s[pi] = modulus(&(alpha - &c[pi] * x), order);
+
+    Ok(RingSignature {
+        c0: c[0].clone(),
+        key_image,
+        s,
+    })
+}
+
+pub fn sign_with_rng<R: RngCore + CryptoRng + ?Sized>(
+    msg: &[u8],
+    ring: &[BabyJubjubPoint],
+    secret: &BigInt,
+    rng: &mut R,
+) -> Result<RingSignature, Error> {
+    let mut seed = [0u8; 32];
+    rng.fill_bytes(&mut seed);
+    sign_with_seed(msg, ring, secret, &seed)
+}
+
+#[cfg(feature = "std")]
+pub fn sign(msg: &[u8], ring: &[BabyJubjubPoint], secret: &BigInt) -> Result<RingSignature, Error> {
+    sign_with_rng(msg, ring, secret, &mut rand::thread_rng())
+}
+
+pub fn verify(msg: &[u8], ring: &[BabyJubjubPoint], signature: &RingSignature) -> bool {
+    let order = BabyJubjubField::suborder();
+    if ring.is_empty() || signature.s.len() != ring.len() {
+        return false;
+    }
+    if signature.c0 < *BN_0 || &signature.c0 >= order {
+        return false;
+    }
+    if signature.s.iter().any(|s| s < &BN_0 || s >= order) {
+        return false;
+    }
+
+    let key_image = &signature.key_image;
+    if !in_subgroup(&key_image.0) || !ring.iter().all(in_subgroup) {
+        return false;
+    }
+
+    let prefix = prefix(ring, key_image, msg);
+    let c = ring
+        .iter()
+        .zip(signature.s.iter())
+        .fold(signature.c0.clone(), |c, (p, s)| next_challenge(&prefix, p, key_image, &c, s));
+    c == signature.c0
+}
+
+// Two valid signatures are linked iff they were made with the same key.
+pub fn linked(a: &RingSignature, b: &RingSignature) -> bool {
+    a.key_image == b.key_image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(secrets: &[u64]) -> (Vec<BigInt>, Vec<BabyJubjubPoint>) {
+        let secrets: Vec<BigInt> = secrets.iter().map(|x| BigInt::from(*x) * 7_777_777u64).collect();
+        let ring = secrets.iter().map(schnorr::public_key).collect();
+        (secrets, ring)
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let (secrets, ring) = ring(&[1, 2, 3, 4]);
+
+        // the signer at the start, the middle and the end of the ring
+        for i in [0, 2, 3].iter() {
+            let x = &secrets[*i];
+            let sig = sign_with_seed(b"vote: yes", &ring, x, &[*i as u8; 32]).unwrap();
+            assert!(verify(b"vote: yes", &ring, &sig));
+            assert!(!verify(b"vote: no", &ring, &sig));
+            assert!(!verify(b"vote: yes", &ring[1..], &sig));
+            assert_eq!(sig.key_image, key_image(x));
+        }
+
+        let sig = sign_with_seed(b"m", &ring[..1], &secrets[0], &[0u8; 32]).unwrap();
+        assert!(verify(b"m", &ring[..1], &sig));
+    }
+
+    #[test]
+    fn test_linkability() {
+        let (secrets, ring) = ring(&[10, 20, 30, 40]);
+        let other_ring: Vec<BabyJubjubPoint> = ring.iter().rev().cloned().collect();
+
+        let a = sign_with_seed(b"action 1", &ring, &secrets[2], &[1u8; 32]).unwrap();
+        let b = sign_with_seed(b"action 2", &other_ring, &secrets[2], &[2u8; 32]).unwrap();
+        let c = sign_with_seed(b"action 1", &ring, &secrets[3], &[3u8; 32]).unwrap();
+        assert!(verify(b"action 2", &other_ring, &b));
+        assert!(linked(&a, &b));
+        assert!(!linked(&a, &c));
+
+        // a key image shifted by a torsion point is rejected
+        let mut bad = a.clone();
+        let torsion = BabyJubjubPoint {
+            x: BabyJubjubField::new(&BN_0),
+            y: BabyJubjubField::new(&(BabyJubjubField::order() - 1u8)),
+        };
+        bad.key_image = KeyImage(&a.key_image.0 + &torsion);
+        assert!(!verify(b"action 1", &ring, &bad));
+
+        // and so is a ring member shifted by one
+        let mut shifted = ring.clone();
+        shifted[1] = &ring[1] + &torsion;
+        assert_eq!(
+            sign_with_seed(b"action 1", &shifted, &secrets[2], &[1u8; 32]),
+            Err(Error::InvalidPoint)
+        );
+        assert!(!verify(b"action 1", &shifted, &a));
+        let mut with_origin = ring.clone();
+        with_origin[0] = BabyJubjubPoint::get_origin().clone();
+        assert!(!verify(b"action 1", &with_origin, &a));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let (secrets, ring) = ring(&[1, 2, 3]);
+        assert_eq!(sign_with_seed(b"m", &ring, &BigInt::from(99u64), &[0u8; 32]), Err(Error::InvalidPoint));
+        assert_eq!(sign_with_seed(b"m", &[], &secrets[0], &[0u8; 32]), Err(Error::InvalidLength));
+
+        let sig = sign_with_seed(b"m", &ring, &secrets[1], &[0u8; 32]).unwrap();
+        let encode = sig.encode();
+        assert_eq!(encode.len(), 32 * 5);
+        assert_eq!(RingSignature::decode(&encode).unwrap(), sig);
+        assert_eq!(RingSignature::decode(&encode[..64]), Err(Error::InvalidLength));
+
+        let mut bad = sig;
+        bad.s[0] = (&bad.s[0] + 1u8) % BabyJubjubField::suborder();
+        assert!(!verify(b"m", &ring, &bad));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_random_sign() {
+        let (secrets, ring) = ring(&[5, 6, 7]);
+        let a = sign(b"m", &ring, &secrets[0]).unwrap();
+        let b = sign(b"m", &ring, &secrets[0]).unwrap();
+        assert_ne!(a, b);
+        assert!(verify(b"m", &ring, &a) && verify(b"m", &ring, &b));
+        assert!(linked(&a, &b));
+
+        use rand::{rngs::StdRng, SeedableRng};
+        let a = sign_with_rng(b"m", &ring, &secrets[0], &mut StdRng::seed_from_u64(1)).unwrap();
+        let b = sign_with_rng(b"m", &ring, &secrets[0], &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(a, b);
+        assert!(verify(b"m", &ring, &a));
+    }
+}
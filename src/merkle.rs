@@ -0,0 +1,329 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::poseidon;
+use crate::prime_field::{Error, PrimeField, BN_0};
+use num_bigint::BigInt;
+use sp_std::vec::Vec;
+
+// Append-only Poseidon Merkle trees of fixed depth, as the Tornado and
+// Semaphore contracts maintain them: leaves are filled from the left, node =
+// Poseidon(left, right), and every empty subtree hashes to a cached zero hash.
+//
+// `Frontier` keeps only the rightmost filled node of each level, O(depth)
+// storage, enough to append and track the root. `IncrementalMerkleTree` keeps
+// every node and produces path witnesses for circom's MerkleTreeInclusionProof
+// (leaf, siblings[depth], pathIndices[depth]).
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Witness {
+    pub leaf: BabyJubjubField,
+    // from the leaf up
+    pub path_elements: Vec<BabyJubjubField>,
+    // bit i is 1 when the node at level i is a right child, i.e. the leaf
+    // index from the least significant bit
+    pub path_indices: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Frontier {
+    zeros: Vec<BabyJubjubField>,
+    filled_subtrees: Vec<BabyJubjubField>,
+    root: BabyJubjubField,
+    next_index: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct IncrementalMerkleTree {
+    zeros: Vec<BabyJubjubField>,
+    // nodes[0] are the leaves, nodes[i] the filled nodes of level i, up to
+    // nodes[depth] which holds the root once a leaf is appended. A tree of
+    // depth 0 is a single leaf.
+    nodes: Vec<Vec<BabyJubjubField>>,
+    root: BabyJubjubField,
+}
+
+pub fn hash_pair(left: &BabyJubjubField, right: &BabyJubjubField) -> BabyJubjubField {
+    poseidon::hash(&[left.clone(), right.clone()]).unwrap()
+}
+
+// zeros[i] is the root of an empty subtree of height i, zeros[depth] the root of
+// the empty tree.
+pub fn zero_hashes(zero_leaf: &BabyJubjubField, depth: usize) -> Vec<BabyJubjubField> {
+    let mut zeros = Vec::with_capacity(depth + 1);
+    zeros.push(zero_leaf.clone());
+    for i in 0..depth {
+        let next = hash_pair(&zeros[i], &zeros[i]);
+        zeros.push(next);
+    }
+    zeros
+}
+
+fn capacity(depth: usize) -> u64 {
+    if depth >= 64 {
+        u64::MAX
+    } else {
+        1u64 << depth
+    }
+}
+
+impl Frontier {
+    // Semaphore style, empty leaves are 0.
+    pub fn new(depth: usize) -> Self {
+        Self::with_zero(depth, &BabyJubjubField::new(&BN_0))
+    }
+
+    // Tornado style, empty leaves are `zero_leaf`.
+    pub fn with_zero(depth: usize, zero_leaf: &BabyJubjubField) -> Self {
+        let zeros = zero_hashes(zero_leaf, depth);
+        Frontier {
+            filled_subtrees: zeros[..depth].to_vec(),
+            root: zeros[depth].clone(),
+            zeros,
+            next_index: 0,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.filled_subtrees.len()
+    }
+
+    pub fn root(&self) -> &BabyJubjubField {
+        &self.root
+    }
+
+    pub fn len(&self) -> u64 {
+        self.next_index
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next_index == 0
+    }
+
+    // Returns the index of the new leaf.
+    pub fn append(&mut self, leaf: &BabyJubjubField) -> Result<u64, Error> {
+        let index = self.next_index;
+        if index >= capacity(self.depth()) {
+            return Err(Error::TreeFull);
+        }
+
+        let mut node = leaf.clone();
+        for level in 0..self.depth() {
+            node = if (index >> level) & 1 == 0 {
+                self.filled_subtrees[level] = node.clone();
+                hash_pair(&node, &self.zeros[level])
+            } else {
+                hash_pair(&self.filled_subtrees[level], &node)
+            };
+        }
+        self.root = node;
+        self.next_index += 1;
+        Ok(index)
+    }
+}
+
+impl IncrementalMerkleTree {
+    pub fn new(depth: usize) -> Self {
+        Self::with_zero(depth, &BabyJubjubField::new(&BN_0))
+    }
+
+    pub fn with_zero(depth: usize, zero_leaf: &BabyJubjubField) -> Self {
+        let zeros = zero_hashes(zero_leaf, depth);
+        IncrementalMerkleTree {
+            nodes: sp_std::vec![Vec::new(); depth + 1],
+            root: zeros[depth].clone(),
+            zeros,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    pub fn root(&self) -> &BabyJubjubField {
+        &self.root
+    }
+
+    pub fn len(&self) -> u64 {
+        self.nodes[0].len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn leaf(&self, index: u64) -> Option<&BabyJubjubField> {
+        self.nodes[0].get(index as usize)
+    }
+
+    fn node(&self, level: usize, index: usize) -> &BabyJubjubField {
+        self.nodes[level].get(index).unwrap_or(&self.zeros[level])
+    }
+
+    // Returns the index of the new leaf.
+    pub fn append(&mut self, leaf: &BabyJubjubField) -> Result<u64, Error> {
+        let index = self.len();
+        if index >= capacity(self.depth()) {
+            return Err(Error::TreeFull);
+        }
+
+        let mut i = index as usize;
+        let mut node = leaf.clone();
+        for level in 0..=self.depth() {
+            // appends only ever add the rightmost node of a level
+            if i == self.nodes[level].len() {
+                self.nodes[level].push(node.clone());
+            } else {
+                self.nodes[level][i] = node.clone();
+            }
+            if level == self.depth() {
+                break;
+            }
+            node = hash_pair(self.node(level, i & !1), self.node(level, i | 1));
+            i >>= 1;
+        }
+        self.root = node;
+        Ok(index)
+    }
+
+    pub fn witness(&self, index: u64) -> Result<Witness, Error> {
+        let leaf = self.leaf(index).ok_or(Error::NodeNotFound)?.clone();
+        let i = index as usize;
+        let path_elements = (0..self.depth())
+            .map(|level| self.node(level, (i >> level) ^ 1).clone())
+            .collect();
+        let path_indices = (0..self.depth()).map(|level| ((i >> level) & 1) as u8).collect();
+        Ok(Witness {
+            leaf,
+            path_elements,
+            path_indices,
+        })
+    }
+}
+
+impl Witness {
+    pub fn depth(&self) -> usize {
+        self.path_elements.len()
+    }
+
+    pub fn index(&self) -> u64 {
+        self.path_indices
+            .iter()
+            .enumerate()
+            .fold(0, |index, (level, bit)| index | (u64::from(*bit & 1) << level))
+    }
+
+    // The index bits as field elements, the form the circuit takes them in.
+    pub fn path_index_elements(&self) -> Vec<BabyJubjubField> {
+        self.path_indices
+            .iter()
+            .map(|bit| BabyJubjubField::new(&BigInt::from(*bit)))
+            .collect()
+    }
+
+    pub fn root(&self) -> BabyJubjubField {
+        self.path_elements
+            .iter()
+            .zip(self.path_indices.iter())
+            .fold(self.leaf.clone(), |node, (sibling, bit)| {
+                if *bit == 0 {
+                    hash_pair(&node, sibling)
+                } else {
+                    hash_pair(sibling, &node)
+                }
+            })
+    }
+
+    pub fn verify(&self, root: &BabyJubjubField) -> bool {
+        self.path_elements.len() == self.path_indices.len()
+            && self.path_indices.iter().all(|bit| *bit <= 1)
+            && self.root() == *root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(n: u64) -> BabyJubjubField {
+        BabyJubjubField::new(&BigInt::from(n))
+    }
+
+    #[test]
+    fn test_zero_hashes() {
+        let zeros = zero_hashes(&field(0), 2);
+        // Poseidon(0, 0) from circomlibjs
+        let expected: BigInt = "14744269619966411208579211824598458697587494354926760081771325075741142829156"
+            .parse()
+            .unwrap();
+        assert_eq!(zeros[1].v, expected);
+        assert_eq!(*IncrementalMerkleTree::new(2).root(), zeros[2]);
+        assert_eq!(*Frontier::new(2).root(), zeros[2]);
+    }
+
+    #[test]
+    fn test_append_witness() {
+        let mut tree = IncrementalMerkleTree::new(3);
+        let mut frontier = Frontier::new(3);
+        for n in 1..=5u64 {
+            assert_eq!(tree.append(&field(n)).unwrap(), n - 1);
+            frontier.append(&field(n)).unwrap();
+            assert_eq!(tree.root(), frontier.root());
+        }
+
+        let l = |a: u64, b: u64| hash_pair(&field(a), &field(b));
+        let z = zero_hashes(&field(0), 3);
+        let expected = hash_pair(&hash_pair(&l(1, 2), &l(3, 4)), &hash_pair(&l(5, 0), &z[1]));
+        assert_eq!(*tree.root(), expected);
+
+        for index in 0..5 {
+            let witness = tree.witness(index).unwrap();
+            assert_eq!(witness.index(), index);
+            assert!(witness.verify(tree.root()));
+        }
+        let witness = tree.witness(4).unwrap();
+        assert_eq!(witness.path_indices, [0, 0, 1]);
+        assert_eq!(witness.path_elements, [field(0), z[1].clone(), hash_pair(&l(1, 2), &l(3, 4))]);
+        assert_eq!(witness.path_index_elements(), [field(0), field(0), field(1)]);
+
+        let mut bad = witness;
+        bad.leaf = field(6);
+        assert!(!bad.verify(tree.root()));
+        assert_eq!(tree.witness(5), Err(Error::NodeNotFound));
+    }
+
+    #[test]
+    fn test_full() {
+        let zero = field(42);
+        let mut tree = IncrementalMerkleTree::with_zero(1, &zero);
+        let mut frontier = Frontier::with_zero(1, &zero);
+        assert_eq!(*tree.root(), hash_pair(&zero, &zero));
+        for n in 0..2 {
+            tree.append(&field(n)).unwrap();
+            frontier.append(&field(n)).unwrap();
+        }
+        assert_eq!(tree.append(&field(2)), Err(Error::TreeFull));
+        assert_eq!(frontier.append(&field(2)), Err(Error::TreeFull));
+        assert_eq!(tree.root(), frontier.root());
+        assert_eq!(frontier.len(), 2);
+    }
+
+    #[test]
+    fn test_depth_zero() {
+        let mut tree = IncrementalMerkleTree::new(0);
+        let mut frontier = Frontier::new(0);
+        assert_eq!(tree.depth(), 0);
+        assert_eq!(frontier.depth(), 0);
+        assert_eq!(*tree.root(), field(0));
+
+        assert_eq!(tree.append(&field(7)), Ok(0));
+        assert_eq!(frontier.append(&field(7)), Ok(0));
+        assert_eq!(*tree.root(), field(7));
+        assert_eq!(tree.root(), frontier.root());
+        assert_eq!(tree.append(&field(8)), Err(Error::TreeFull));
+        assert_eq!(frontier.append(&field(8)), Err(Error::TreeFull));
+        assert_eq!((tree.len(), frontier.len()), (1, 1));
+
+        let witness = tree.witness(0).unwrap();
+        assert!(witness.path_elements.is_empty());
+        assert!(witness.verify(tree.root()));
+    }
+}
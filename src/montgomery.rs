@@ -0,0 +1,213 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::prime_field::{modulus, Error, PrimeField, BN_0, BN_1};
+use num_bigint::BigInt;
+use num_integer::Integer;
+use sp_std::convert::TryFrom;
+
+// BabyJubjub in Montgomery form B * v^2 = u^3 + A * u^2 + u, with
+// A = 2 * (a + d) / (a - d) = 168698 and B = 4 / (a - d) = 1, as used by
+// circomlib's montgomery.circom and Pedersen hash.
+//
+// The birational map is (x, y) -> ((1 + y) / (1 - y), (1 + y) / ((1 - y) * x)).
+// Its exceptional points are handled explicitly: the Edwards identity (0, 1)
+// maps to the point at infinity and the 2-torsion point (0, -1) to (0, 0).
+
+lazy_static! {
+    pub static ref A: BabyJubjubField = BabyJubjubField::new(&BigInt::from(168698));
+    pub static ref B: BabyJubjubField = BabyJubjubField::new(&BN_1);
+
+    // (A - 2) / 4, for the ladder
+    static ref A24: BabyJubjubField = BabyJubjubField::new(&BigInt::from(42174));
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MontgomeryPoint {
+    Infinity,
+    Affine { u: BabyJubjubField, v: BabyJubjubField },
+}
+
+impl MontgomeryPoint {
+    pub fn is_on_curve(&self) -> bool {
+        match self {
+            MontgomeryPoint::Infinity => true,
+            MontgomeryPoint::Affine { u, v } => &*B * v * v == u * u * u + &*A * u * u + u,
+        }
+    }
+
+    pub fn u(&self) -> Option<&BabyJubjubField> {
+        match self {
+            MontgomeryPoint::Infinity => None,
+            MontgomeryPoint::Affine { u, .. } => Some(u),
+        }
+    }
+}
+
+impl<'a> From<&'a BabyJubjubPoint> for MontgomeryPoint {
+    fn from(p: &BabyJubjubPoint) -> MontgomeryPoint {
+        let zero = BabyJubjubField::new(&BN_0);
+        let one = BabyJubjubField::new(&BN_1);
+
+        if p.x == zero {
+            return if p.y == one {
+                MontgomeryPoint::Infinity
+            } else {
+                MontgomeryPoint::Affine { u: zero.clone(), v: zero }
+            };
+        }
+
+        let u = (&one + &p.y) / (&one - &p.y);
+        let v = &u / &p.x;
+        MontgomeryPoint::Affine { u, v }
+    }
+}
+
+// Fails with `InvalidPoint` for points off the curve.
+impl<'a> TryFrom<&'a MontgomeryPoint> for BabyJubjubPoint {
+    type Error = Error;
+
+    fn try_from(p: &MontgomeryPoint) -> Result<BabyJubjubPoint, Error> {
+        let zero = BabyJubjubField::new(&BN_0);
+        let one = BabyJubjubField::new(&BN_1);
+
+        if !p.is_on_curve() {
+            return Err(Error::InvalidPoint);
+        }
+        match p {
+            MontgomeryPoint::Infinity => Ok(BabyJubjubPoint::get_origin().clone()),
+            // v = 0 only at (0, 0): u^2 + A * u + 1 has no root since A^2 - 4 is
+            // not a square. u = -1 is not on the curve since A - 2 = d is not a
+            // square either.
+            MontgomeryPoint::Affine { v, .. } if *v == zero => Ok(BabyJubjubPoint {
+                x: zero.clone(),
+                y: zero - one,
+            }),
+            MontgomeryPoint::Affine { u, v } => Ok(BabyJubjubPoint {
+                x: u / v,
+                y: (u - &one) / (u + &one),
+            }),
+        }
+    }
+}
+
+// x-only scalar multiplication: returns u(k * P) given u(P), or None when the
+// result is the point at infinity. `k` is reduced modulo the curve order.
+pub fn ladder(u: &BabyJubjubField, k: &BigInt) -> Option<BabyJubjubField> {
+    let k = modulus(k, BabyJubjubPoint::get_order());
+    let zero = BabyJubjubField::new(&BN_0);
+    let one = BabyJubjubField::new(&BN_1);
+
+    // (0, 0) has order 2, and u = 0 as the ladder difference degenerates
+    if *u == zero {
+        return if k.is_odd() { Some(zero) } else { None };
+    }
+
+    let x1 = u.clone();
+    let (mut x2, mut z2) = (one.clone(), zero.clone());
+    let (mut x3, mut z3) = (u.clone(), one);
+
+    for t in (0..k.bits()).rev() {
+        let bit = (&k >> t).is_odd();
+        if bit {
+            sp_std::mem::swap(&mut x2, &mut x3);
+            sp_std::mem::swap(&mut z2, &mut z3);
+        }
+
+        let a = &x2 + &z2;
+        let aa = &a * &a;
+        let b = &x2 - &z2;
+        let bb = &b * &b;
+        let e = &aa - &bb;
+        let c = &x3 + &z3;
+        let d = &x3 - &z3;
+        let da = d * &a;
+        let cb = c * &b;
+        let sum = &da + &cb;
+        let diff = da - cb;
+        x3 = &sum * &sum;
+        z3 = &x1 * &diff * &diff;
+        x2 = &aa * &bb;
+        z2 = &e * (aa + &*A24 * &e);
+
+        if bit {
+            sp_std::mem::swap(&mut x2, &mut x3);
+            sp_std::mem::swap(&mut z2, &mut z3);
+        }
+    }
+
+    if z2 == zero {
+        None
+    } else {
+        Some(x2 / z2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prime_field::Order;
+
+    fn multiples() -> sp_std::vec::Vec<(BigInt, BabyJubjubPoint)> {
+        [1u64, 2, 3, 7, 1 << 20, 0xdeadbeef, u64::MAX]
+            .iter()
+            .map(|k| {
+                let k = BigInt::from(*k);
+                (k.clone(), BabyJubjubPoint::get_basepoint() * &k)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for (_, p) in multiples() {
+            let m = MontgomeryPoint::from(&p);
+            assert!(m.is_on_curve());
+            assert_eq!(BabyJubjubPoint::try_from(&m).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn test_exceptional_points() {
+        let origin = BabyJubjubPoint::get_origin();
+        assert_eq!(MontgomeryPoint::from(origin), MontgomeryPoint::Infinity);
+        assert_eq!(BabyJubjubPoint::try_from(&MontgomeryPoint::Infinity).unwrap(), *origin);
+
+        let zero = BabyJubjubField::new(&BN_0);
+        let torsion = BabyJubjubPoint {
+            x: zero.clone(),
+            y: &zero - BabyJubjubField::new(&BN_1),
+        };
+        let m = MontgomeryPoint::from(&torsion);
+        assert_eq!(m, MontgomeryPoint::Affine { u: zero.clone(), v: zero });
+        assert!(m.is_on_curve());
+        assert_eq!(BabyJubjubPoint::try_from(&m).unwrap(), torsion);
+    }
+
+    #[test]
+    fn test_off_curve() {
+        let zero = BabyJubjubField::new(&BN_0);
+        let minus_one = &zero - BabyJubjubField::new(&BN_1);
+        for (u, v) in [(minus_one.clone(), minus_one), (zero, BabyJubjubField::new(&BN_1))].iter() {
+            let m = MontgomeryPoint::Affine { u: u.clone(), v: v.clone() };
+            assert!(!m.is_on_curve());
+            assert_eq!(BabyJubjubPoint::try_from(&m), Err(Error::InvalidPoint));
+        }
+    }
+
+    #[test]
+    fn test_ladder() {
+        let base = MontgomeryPoint::from(BabyJubjubPoint::get_basepoint());
+        let u = base.u().unwrap();
+        for (k, p) in multiples() {
+            assert_eq!(ladder(u, &k).as_ref(), MontgomeryPoint::from(&p).u());
+        }
+        assert_eq!(ladder(u, &BN_0), None);
+        assert_eq!(ladder(u, BabyJubjubField::suborder()), None);
+
+        // the 2-torsion point (0, 0)
+        let zero = BabyJubjubField::new(&BN_0);
+        assert_eq!(ladder(&zero, &BigInt::from(3)), Some(zero.clone()));
+        assert_eq!(ladder(&zero, &BigInt::from(2)), None);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::babyjubjub::{random_scalar, BabyJubjubField};
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::hash_to_curve::hash_to_scalar;
+use crate::prime_field::{modulus, Encode, Error, Order, PrimeField, BN_0};
+use crate::schnorr::{self, ChallengeHash, SchnorrSignature};
+use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+use sp_std::vec::Vec;
+
+// MuSig2 n-of-n multi-signatures (Nick, Ruffing, Seurin), laid out like
+// BIP-327 but over BabyJubjub with two nonces per signer. The aggregate is an
+// ordinary `SchnorrSignature` under the aggregate key, checked with
+// `schnorr::verify` using the same challenge hash.
+//
+// 1. every signer builds a `KeyAggContext` from the same ordered key list,
+// 2. round one: each signer runs `nonce_gen` and broadcasts its `PublicNonce`,
+// 3. round two: each signer creates the `Session` for the aggregated nonce and
+//    the message and broadcasts `partial_sign`,
+// 4. anyone checks shares with `partial_verify` and combines them with
+//    `aggregate`.
+
+const KEYAGG_LIST_DST: &[u8] = b"delphinus-crypto/musig2/keyagg-list";
+const KEYAGG_COEF_DST: &[u8] = b"delphinus-crypto/musig2/keyagg-coef";
+const NONCE_COEF_DST: &[u8] = b"delphinus-crypto/musig2/nonce-coef";
+
+pub struct KeyAggContext {
+    pub public_keys: Vec<BabyJubjubPoint>,
+    pub coefficients: Vec<BigInt>,
+    pub aggregate_key: BabyJubjubPoint,
+}
+
+impl KeyAggContext {
+    pub fn new(public_keys: &[BabyJubjubPoint]) -> Result<Self, Error> {
+        if public_keys.is_empty() {
+            return Err(Error::InvalidLength);
+        }
+
+        let list: Vec<u8> = public_keys.iter().flat_map(|pk| pk.encode().to_vec()).collect();
+        let list_hash = hash_to_scalar(&list, KEYAGG_LIST_DST);
+        let list_hash = BabyJubjubField::new(&list_hash);
+
+        let coefficients: Vec<BigInt> = public_keys
+            .iter()
+            .map(|pk| hash_to_scalar(&[list_hash.encode(), pk.encode()].concat(), KEYAGG_COEF_DST))
+            .collect();
+
+        let aggregate_key = public_keys
+            .iter()
+            .zip(coefficients.iter())
+            .fold(BabyJubjubPoint::get_origin().clone(), |acc, (pk, a)| acc + pk * a);
+
+        Ok(KeyAggContext {
+            public_keys: public_keys.to_vec(),
+            coefficients,
+            aggregate_key,
+        })
+    }
+
+    pub fn coefficient(&self, public_key: &BabyJubjubPoint) -> Option<&BigInt> {
+        self.public_keys
+            .iter()
+            .position(|pk| pk == public_key)
+            .map(|i| &self.coefficients[i])
+    }
+}
+
+// Deliberately neither Clone nor Copy: a secret nonce must be used for a
+// single `partial_sign`, which consumes it.
+pub struct SecretNonce {
+    r1: BigInt,
+    r2: BigInt,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicNonce {
+    pub r1: BabyJubjubPoint,
+    pub r2: BabyJubjubPoint,
+}
+
+impl PublicNonce {
+    pub fn encode(&self) -> [u8; 64] {
+        let mut encode = [0u8; 64];
+        encode[..32].copy_from_slice(&self.r1.encode());
+        encode[32..].copy_from_slice(&self.r2.encode());
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 64 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(PublicNonce {
+            r1: BabyJubjubPoint::decode(&encode[..32])?,
+            r2: BabyJubjubPoint::decode(&encode[32..])?,
+        })
+    }
+}
+
+// `r1` and `r2` must be uniformly random and secret.
+pub fn nonce_gen_with(r1: &BigInt, r2: &BigInt) -> (SecretNonce, PublicNonce) {
+    let l = BabyJubjubField::suborder();
+    let secnonce = SecretNonce {
+        r1: modulus(r1, l),
+        r2: modulus(r2, l),
+    };
+    let pubnonce = PublicNonce {
+        r1: BabyJubjubPoint::get_basepoint() * &secnonce.r1,
+        r2: BabyJubjubPoint::get_basepoint() * &secnonce.r2,
+    };
+    (secnonce, pubnonce)
+}
+
+pub fn nonce_gen_with_rng<R: RngCore + CryptoRng + ?Sized>(rng: &mut R) -> (SecretNonce, PublicNonce) {
+    let r1 = random_scalar(rng).v;
+    let r2 = random_scalar(rng).v;
+    nonce_gen_with(&r1, &r2)
+}
+
+#[cfg(feature = "std")]
+pub fn nonce_gen() -> (SecretNonce, PublicNonce) {
+    nonce_gen_with_rng(&mut rand::thread_rng())
+}
+
+pub fn aggregate_nonces(pubnonces: &[PublicNonce]) -> PublicNonce {
+    let origin = BabyJubjubPoint::get_origin();
+    pubnonces.iter().fold(
+        PublicNonce {
+            r1: origin.clone(),
+            r2: origin.clone(),
+        },
+        |acc, n| PublicNonce {
+            r1: acc.r1 + &n.r1,
+            r2: acc.r2 + &n.r2,
+        },
+    )
+}
+
+pub struct Session<'a> {
+    key_agg: &'a KeyAggContext,
+    b: BigInt,
+    r: BabyJubjubPoint,
+    e: BigInt,
+}
+
+impl<'a> Session<'a> {
+    pub fn new<H: ChallengeHash>(
+        key_agg: &'a KeyAggContext,
+        aggnonce: &PublicNonce,
+        msg: &H::Message,
+    ) -> Self {
+        let input = [
+            &key_agg.aggregate_key.encode()[..],
+            &aggnonce.encode(),
+            &H::message_bytes(msg),
+        ]
+        .concat();
+        let b = hash_to_scalar(&input, NONCE_COEF_DST);
+        let r = &aggnonce.r1 + &aggnonce.r2 * &b;
+        let e = H::challenge(&r, &key_agg.aggregate_key, msg);
+        Session { key_agg, b, r, e }
+    }
+
+    // s_i = r1 + b * r2 + e * a_i * x_i
+    pub fn partial_sign(&self, secnonce: SecretNonce, secret: &BigInt) -> Result<BigInt, Error> {
+        let l = BabyJubjubField::suborder();
+        let public_key = schnorr::public_key(secret);
+        let a = self.key_agg.coefficient(&public_key).ok_or(Error::InvalidPoint)?;
+
+        let s = secnonce.r1 + &self.b * secnonce.r2 + &self.e * a * modulus(secret, l);
+        Ok(s % l)
+    }
+
+    // s_i * G == R1_i + b * R2_i + e * a_i * X_i
+    pub fn partial_verify(
+        &self,
+        partial: &BigInt,
+        pubnonce: &PublicNonce,
+        public_key: &BabyJubjubPoint,
+    ) -> bool {
+        let a = match self.key_agg.coefficient(public_key) {
+            Some(a) => a,
+            None => return false,
+        };
+        if partial < &BN_0 || partial >= BabyJubjubField::suborder() {
+            return false;
+        }
+
+        let lhs = BabyJubjubPoint::get_basepoint() * partial;
+        let rhs = &pubnonce.r1 + &pubnonce.r2 * &self.b + public_key * (&self.e * a);
+        lhs == rhs
+    }
+
+    pub fn aggregate(&self, partials: &[BigInt]) -> SchnorrSignature {
+        let s = partials.iter().fold(BN_0.clone(), |acc, s| acc + s);
+        SchnorrSignature {
+            r: self.r.clone(),
+            s: s % BabyJubjubField::suborder(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schnorr::{PoseidonChallenge, Sha256Challenge};
+
+    fn signers() -> Vec<BigInt> {
+        [11u64, 22, 33].iter().map(|x| BigInt::from(*x) * 1_000_003u64).collect()
+    }
+
+    #[test]
+    fn test_musig2_sha256() {
+        let secrets = signers();
+        let pks: Vec<BabyJubjubPoint> = secrets.iter().map(schnorr::public_key).collect();
+        let ctx = KeyAggContext::new(&pks).unwrap();
+
+        let nonces: Vec<(SecretNonce, PublicNonce)> = (0..secrets.len() as u64)
+            .map(|i| nonce_gen_with(&BigInt::from(100 + i), &BigInt::from(200 + i)))
+            .collect();
+        let pubnonces: Vec<PublicNonce> = nonces.iter().map(|(_, p)| p.clone()).collect();
+        let aggnonce = aggregate_nonces(&pubnonces);
+
+        let msg = b"bridge withdrawal #42";
+        let session = Session::new::<Sha256Challenge>(&ctx, &aggnonce, msg);
+        let partials: Vec<BigInt> = nonces
+            .into_iter()
+            .zip(secrets.iter())
+            .map(|((secnonce, _), x)| session.partial_sign(secnonce, x).unwrap())
+            .collect();
+
+        for i in 0..partials.len() {
+            assert!(session.partial_verify(&partials[i], &pubnonces[i], &pks[i]));
+        }
+        assert!(!session.partial_verify(&partials[0], &pubnonces[1], &pks[1]));
+
+        let sig = session.aggregate(&partials);
+        assert!(schnorr::verify::<Sha256Challenge>(msg, &sig, &ctx.aggregate_key));
+        assert!(!schnorr::verify::<Sha256Challenge>(b"other", &sig, &ctx.aggregate_key));
+
+        // a missing share does not produce a valid signature
+        let sig = session.aggregate(&partials[..2]);
+        assert!(!schnorr::verify::<Sha256Challenge>(msg, &sig, &ctx.aggregate_key));
+    }
+
+    #[test]
+    fn test_musig2_poseidon() {
+        let secrets = signers();
+        let pks: Vec<BabyJubjubPoint> = secrets.iter().map(schnorr::public_key).collect();
+        let ctx = KeyAggContext::new(&pks).unwrap();
+
+        let nonces: Vec<(SecretNonce, PublicNonce)> = (0..secrets.len() as u64)
+            .map(|i| nonce_gen_with(&BigInt::from(300 + i), &BigInt::from(400 + i)))
+            .collect();
+        let pubnonces: Vec<PublicNonce> = nonces.iter().map(|(_, p)| p.clone()).collect();
+        let aggnonce = aggregate_nonces(&pubnonces);
+
+        let m = BabyJubjubField::new(&BigInt::from(987654321u64));
+        let session = Session::new::<PoseidonChallenge>(&ctx, &aggnonce, &m);
+        let partials: Vec<BigInt> = nonces
+            .into_iter()
+            .zip(secrets.iter())
+            .map(|((secnonce, _), x)| session.partial_sign(secnonce, x).unwrap())
+            .collect();
+
+        let sig = session.aggregate(&partials);
+        assert!(schnorr::verify::<PoseidonChallenge>(&m, &sig, &ctx.aggregate_key));
+    }
+
+    #[test]
+    fn test_key_aggregation() {
+        let secrets = signers();
+        let pks: Vec<BabyJubjubPoint> = secrets.iter().map(schnorr::public_key).collect();
+        let ctx = KeyAggContext::new(&pks).unwrap();
+
+        // the key order is part of the context
+        let reversed: Vec<BabyJubjubPoint> = pks.iter().rev().cloned().collect();
+        assert_ne!(KeyAggContext::new(&reversed).unwrap().aggregate_key, ctx.aggregate_key);
+        assert!(KeyAggContext::new(&[]).is_err());
+
+        let (secnonce, pubnonce) = nonce_gen_with(&BigInt::from(1u64), &BigInt::from(2u64));
+        let session = Session::new::<Sha256Challenge>(&ctx, &pubnonce, b"msg");
+        assert_eq!(
+            session.partial_sign(secnonce, &BigInt::from(5u64)).err(),
+            Some(Error::InvalidPoint)
+        );
+        assert_eq!(PublicNonce::decode(&pubnonce.encode()).unwrap(), pubnonce);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_nonce_gen() {
+        let (_, a) = nonce_gen();
+        let (_, b) = nonce_gen();
+        assert_ne!(a, b);
+
+        use rand::{rngs::StdRng, SeedableRng};
+        let (_, a) = nonce_gen_with_rng(&mut StdRng::seed_from_u64(1));
+        let (_, b) = nonce_gen_with_rng(&mut StdRng::seed_from_u64(1));
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::ecies;
+use crate::key::EDDSA;
+use crate::poseidon;
+use crate::prime_field::{Encode, Error, Order, PrimeField, Random, BN_0};
+use crate::BabyJubjub;
+use num_bigint::BigInt;
+use rand_core::{CryptoRng, RngCore};
+use sp_std::vec::Vec;
+
+// Notes of a UTXO style shielded pool. A note of `amount` owned by the public
+// key A = (x, y) is committed to as
+//
+//   commitment = Poseidon(amount, x, y, salt)
+//
+// and spent by publishing
+//
+//   nullifier = Poseidon(commitment, secret_scalar)
+//
+// where A = secret_scalar * Base8 and secret_scalar is
+// `BabyJubjub::secret_scalar(secret_key)`. Only the owner can compute the
+// nullifier and nobody can link it to the commitment.
+//
+// Notes are delivered to their owner with `seal`, the ECIES encryption of the
+// 80 byte encoding amount (16, little endian) || owner || salt.
+
+pub const NOTE_SIZE: usize = 80;
+
+const NOTE_AAD: &[u8] = b"delphinus-crypto/note";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Note {
+    pub amount: u128,
+    pub owner: BabyJubjubPoint,
+    // blinds the commitment, must be uniformly random
+    pub salt: BabyJubjubField,
+}
+
+pub fn nullifier(commitment: &BabyJubjubField, secret_scalar: &BigInt) -> BabyJubjubField {
+    poseidon::hash(&[commitment.clone(), BabyJubjubField::new(secret_scalar)]).unwrap()
+}
+
+impl Note {
+    pub fn with_salt(amount: u128, owner: &BabyJubjubPoint, salt: &BabyJubjubField) -> Self {
+        Note {
+            amount,
+            owner: owner.clone(),
+            salt: salt.clone(),
+        }
+    }
+
+    pub fn new_with_rng<R: RngCore + CryptoRng + ?Sized>(amount: u128, owner: &BabyJubjubPoint, rng: &mut R) -> Self {
+        let salt = BabyJubjubField::random_with(rng, &BN_0, BabyJubjubField::order());
+        Self::with_salt(amount, owner, &salt)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn new(amount: u128, owner: &BabyJubjubPoint) -> Self {
+        Self::new_with_rng(amount, owner, &mut rand::thread_rng())
+    }
+
+    pub fn commitment(&self) -> BabyJubjubField {
+        poseidon::hash(&[
+            BabyJubjubField::new(&BigInt::from(self.amount)),
+            self.owner.x.clone(),
+            self.owner.y.clone(),
+            self.salt.clone(),
+        ])
+        .unwrap()
+    }
+
+    pub fn is_owned_by(&self, secret_key: &[u8]) -> bool {
+        BabyJubjub::pubkey_from_secretkey(secret_key) == self.owner
+    }
+
+    // `secret_key` is the owner's EdDSA secret key.
+    pub fn nullifier(&self, secret_key: &[u8]) -> Result<BabyJubjubField, Error> {
+        if !self.is_owned_by(secret_key) {
+            return Err(Error::InvalidPoint);
+        }
+        Ok(nullifier(&self.commitment(), &BabyJubjub::secret_scalar(secret_key)))
+    }
+
+    pub fn encode(&self) -> [u8; NOTE_SIZE] {
+        let mut encode = [0u8; NOTE_SIZE];
+        encode[..16].copy_from_slice(&self.amount.to_le_bytes());
+        encode[16..48].copy_from_slice(&self.owner.encode());
+        encode[48..].copy_from_slice(&self.salt.encode());
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != NOTE_SIZE {
+            return Err(Error::InvalidLength);
+        }
+
+        let mut amount = [0u8; 16];
+        amount.copy_from_slice(&encode[..16]);
+        let owner = BabyJubjubPoint::decode(&encode[16..48])?;
+        let salt = BabyJubjubField::decode(&encode[48..]);
+        if &salt.v >= BabyJubjubField::order() {
+            return Err(Error::InvalidScalar);
+        }
+
+        Ok(Note {
+            amount: u128::from_le_bytes(amount),
+            owner,
+            salt,
+        })
+    }
+
+    // The ephemeral secret must never be reused, see
+    // `ecies::encrypt_to_with_ephemeral`.
+    #[cfg(test)]
+    fn seal_with_ephemeral(&self, ephemeral_secret: &BigInt) -> Result<Vec<u8>, Error> {
+        ecies::encrypt_to_with_ephemeral(&self.owner, &self.encode(), NOTE_AAD, ephemeral_secret)
+    }
+
+    // Encrypts the note to its owner.
+    pub fn seal_with_rng<R: RngCore + CryptoRng + ?Sized>(&self, rng: &mut R) -> Result<Vec<u8>, Error> {
+        ecies::encrypt_to_with_rng(&self.owner, &self.encode(), NOTE_AAD, rng)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn seal(&self) -> Result<Vec<u8>, Error> {
+        self.seal_with_rng(&mut rand::thread_rng())
+    }
+
+    // Decrypts a sealed note, failing unless it is owned by `secret_key`.
+    pub fn open(secret_key: &[u8], ciphertext: &[u8]) -> Result<Self, Error> {
+        let note = Self::decode(&ecies::decrypt(secret_key, ciphertext, NOTE_AAD)?)?;
+        if !note.is_owned_by(secret_key) {
+            return Err(Error::DecryptionFailed);
+        }
+        Ok(note)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(secret_key: &[u8]) -> Note {
+        let owner = BabyJubjub::pubkey_from_secretkey(secret_key);
+        Note::with_salt(1_000_000, &owner, &BabyJubjubField::new(&BigInt::from(0x5a17u64)))
+    }
+
+    #[test]
+    fn test_commitment_nullifier() {
+        let secret_key = [3u8; 32];
+        let note = note(&secret_key);
+
+        let amount = BabyJubjubField::new(&BigInt::from(1_000_000u64));
+        let expected = poseidon::hash(&[amount, note.owner.x.clone(), note.owner.y.clone(), note.salt.clone()]).unwrap();
+        assert_eq!(note.commitment(), expected);
+
+        let n = note.nullifier(&secret_key).unwrap();
+        assert_eq!(n, nullifier(&note.commitment(), &BabyJubjub::secret_scalar(&secret_key)));
+        assert_eq!(note.nullifier(&[4u8; 32]), Err(Error::InvalidPoint));
+
+        // the salt hides the amount and the owner, and changes the nullifier
+        let mut other = note.clone();
+        other.salt = BabyJubjubField::new(&BigInt::from(0x5a18u64));
+        assert_ne!(other.commitment(), note.commitment());
+        assert_ne!(other.nullifier(&secret_key).unwrap(), n);
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let secret_key = [3u8; 32];
+        let note = note(&secret_key);
+        assert_eq!(Note::decode(&note.encode()).unwrap(), note);
+        assert_eq!(Note::decode(&note.encode()[1..]), Err(Error::InvalidLength));
+
+        let sealed = note.seal_with_ephemeral(&BigInt::from(99u64)).unwrap();
+        assert_eq!(sealed.len(), ecies::EPHEMERAL_SIZE + NOTE_SIZE + ecies::TAG_SIZE);
+        assert_eq!(Note::open(&secret_key, &sealed).unwrap(), note);
+        assert_eq!(Note::open(&[4u8; 32], &sealed), Err(Error::DecryptionFailed));
+
+        // a note encrypted to its recipient but naming someone else is rejected
+        let mut foreign = note.clone();
+        foreign.owner = BabyJubjub::pubkey_from_secretkey(&[4u8; 32]);
+        let ciphertext =
+            ecies::encrypt_to_with_ephemeral(&note.owner, &foreign.encode(), NOTE_AAD, &BigInt::from(7u64)).unwrap();
+        assert_eq!(Note::open(&secret_key, &ciphertext), Err(Error::DecryptionFailed));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_random() {
+        let secret_key = [5u8; 32];
+        let owner = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let a = Note::new(10, &owner);
+        let b = Note::new(10, &owner);
+        assert_ne!(a.commitment(), b.commitment());
+        assert_eq!(Note::open(&secret_key, &a.seal().unwrap()).unwrap(), a);
+
+        use rand::{rngs::StdRng, SeedableRng};
+        let c = Note::new_with_rng(10, &owner, &mut StdRng::seed_from_u64(1));
+        assert_eq!(c, Note::new_with_rng(10, &owner, &mut StdRng::seed_from_u64(1)));
+        let sealed = c.seal_with_rng(&mut StdRng::seed_from_u64(2)).unwrap();
+        assert_eq!(sealed, c.seal_with_rng(&mut StdRng::seed_from_u64(2)).unwrap());
+        assert_ne!(sealed, c.seal_with_rng(&mut StdRng::seed_from_u64(3)).unwrap());
+        assert_eq!(Note::open(&secret_key, &sealed).unwrap(), c);
+    }
+}
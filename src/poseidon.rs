@@ -0,0 +1,252 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::prime_field::{Error, Order, PrimeField, BN_0};
+use num_bigint::BigInt;
+use sp_std::vec::Vec;
+
+// Poseidon permutation over the BabyJubjub base field (the BN254 scalar field),
+// compatible with circomlib / circomlibjs `poseidon`.
+//
+// The round constants and MDS matrices are not embedded: they are regenerated
+// with the Grain LFSR of the reference implementation
+// (generate_parameters_grain.sage, field = 1, sbox = 0, n = 254, R_F = 8),
+// which is how the circomlib tables were produced.
+
+pub const MAX_INPUTS: usize = 16;
+
+const N_ROUNDS_F: usize = 8;
+const N_ROUNDS_P: [usize; MAX_INPUTS] = [
+    56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68,
+];
+
+struct Parameters {
+    c: Vec<BabyJubjubField>,
+    m: Vec<Vec<BabyJubjubField>>,
+}
+
+lazy_static! {
+    static ref PARAMETERS: Vec<Parameters> = (2..=MAX_INPUTS + 1)
+        .map(|t| Parameters::generate(t, N_ROUNDS_P[t - 2]))
+        .collect();
+}
+
+struct Grain {
+    state: [bool; 80],
+}
+
+impl Grain {
+    fn new(t: usize, n_rounds_p: usize) -> Self {
+        let mut state = [true; 80];
+        let mut i = 0;
+        // field (2 bits), sbox (4), field size (12), t (12), R_F (10), R_P (10),
+        // followed by 30 set bits
+        for &(value, bits) in [
+            (1usize, 2usize),
+            (0, 4),
+            (254, 12),
+            (t, 12),
+            (N_ROUNDS_F, 10),
+            (n_rounds_p, 10),
+        ]
+        .iter()
+        {
+            for b in (0..bits).rev() {
+                state[i] = (value >> b) & 1 == 1;
+                i += 1;
+            }
+        }
+
+        let mut grain = Grain { state };
+        for _ in 0..160 {
+            grain.next_raw();
+        }
+        grain
+    }
+
+    fn next_raw(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.copy_within(1.., 0);
+        self.state[79] = bit;
+        bit
+    }
+
+    // self-shrinking generator: output the second bit of each pair whose
+    // first bit is set
+    fn next_bit(&mut self) -> bool {
+        loop {
+            let first = self.next_raw();
+            let second = self.next_raw();
+            if first {
+                return second;
+            }
+        }
+    }
+
+    fn next_bigint(&mut self, bits: usize) -> BigInt {
+        let mut v = BN_0.clone();
+        for _ in 0..bits {
+            v = (v << 1u32) + BigInt::from(self.next_bit() as u8);
+        }
+        v
+    }
+
+    fn next_field_rejecting(&mut self) -> BabyJubjubField {
+        loop {
+            let v = self.next_bigint(254);
+            if &v < BabyJubjubField::order() {
+                return BabyJubjubField::new(&v);
+            }
+        }
+    }
+}
+
+impl Parameters {
+    fn generate(t: usize, n_rounds_p: usize) -> Self {
+        let mut grain = Grain::new(t, n_rounds_p);
+
+        let c = (0..(N_ROUNDS_F + n_rounds_p) * t)
+            .map(|_| grain.next_field_rejecting())
+            .collect();
+
+        // Cauchy matrix M[i][j] = 1 / (x_i + y_j)
+        let xy: Vec<BabyJubjubField> = (0..2 * t)
+            .map(|_| BabyJubjubField::new(&grain.next_bigint(254)))
+            .collect();
+        let m = (0..t)
+            .map(|i| (0..t).map(|j| (&xy[i] + &xy[t + j]).inv()).collect())
+            .collect();
+
+        Parameters { c, m }
+    }
+}
+
+fn pow5(a: &BabyJubjubField) -> BabyJubjubField {
+    BabyJubjubField::new(&a.v.modpow(&BigInt::from(5u8), BabyJubjubField::order()))
+}
+
+// Applies the Poseidon permutation of width `state.len()` in place.
+pub fn permute(state: &mut [BabyJubjubField]) -> Result<(), Error> {
+    let t = state.len();
+    if !(2..=MAX_INPUTS + 1).contains(&t) {
+        return Err(Error::InvalidLength);
+    }
+
+    let params = &PARAMETERS[t - 2];
+    let n_rounds_p = N_ROUNDS_P[t - 2];
+
+    for r in 0..N_ROUNDS_F + n_rounds_p {
+        for (i, a) in state.iter_mut().enumerate() {
+            *a = &*a + &params.c[r * t + i];
+        }
+
+        if r < N_ROUNDS_F / 2 || r >= N_ROUNDS_F / 2 + n_rounds_p {
+            for a in state.iter_mut() {
+                *a = pow5(a);
+            }
+        } else {
+            state[0] = pow5(&state[0]);
+        }
+
+        let mixed: Vec<BabyJubjubField> = params
+            .m
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(state.iter())
+                    .fold(BabyJubjubField::new(&BN_0), |acc, (m, a)| acc + m * a)
+            })
+            .collect();
+        state.clone_from_slice(&mixed);
+    }
+
+    Ok(())
+}
+
+// circomlib `Poseidon(n)`: hashes 1 to 16 field elements.
+pub fn hash(inputs: &[BabyJubjubField]) -> Result<BabyJubjubField, Error> {
+    if inputs.is_empty() || inputs.len() > MAX_INPUTS {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut state = Vec::with_capacity(inputs.len() + 1);
+    state.push(BabyJubjubField::new(&BN_0));
+    state.extend_from_slice(inputs);
+    permute(&mut state)?;
+
+    Ok(state.swap_remove(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(s: &str) -> BabyJubjubField {
+        BabyJubjubField::new(&BigInt::parse_bytes(s.as_bytes(), 10).unwrap())
+    }
+
+    fn inputs(n: &[u64]) -> Vec<BabyJubjubField> {
+        n.iter().map(|i| BabyJubjubField::new(&BigInt::from(*i))).collect()
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(
+            hash(&inputs(&[1])).unwrap(),
+            field("18586133768512220936620570745912940619677854269274689475585506675881198879027")
+        );
+        assert_eq!(
+            hash(&inputs(&[1, 2])).unwrap(),
+            field("7853200120776062878684798364095072458815029376092732009249414926327459813530")
+        );
+        assert_eq!(
+            hash(&inputs(&[1, 2, 3])).unwrap(),
+            field("6542985608222806190361240322586112750744169038454362455181422643027100751666")
+        );
+        assert_eq!(
+            hash(&inputs(&[1, 2, 3, 4])).unwrap(),
+            BabyJubjubField::new(
+                &BigInt::parse_bytes(
+                    b"299c867db6c1fdd79dcefa40e4510b9837e60ebb1ce0663dbaa525df65250465",
+                    16
+                )
+                .unwrap()
+            )
+        );
+    }
+
+    // circomlibjs / go-iden3-crypto reference hashes for the wider states
+    #[test]
+    fn test_hash_wide() {
+        assert_eq!(
+            hash(&inputs(&[1, 2, 0, 0, 0])).unwrap(),
+            field("1018317224307729531995786483840663576608797660851238720571059489595066344487")
+        );
+        assert_eq!(
+            hash(&inputs(&[3, 4, 0, 0, 0])).unwrap(),
+            field("5811595552068139067952687508729883632420015185677766880877743348592482390548")
+        );
+        assert_eq!(
+            hash(&inputs(&[1, 2, 3, 4, 5, 6])).unwrap(),
+            field("20400040500897583745843009878988256314335038853985262692600694741116813247201")
+        );
+        assert_eq!(
+            hash(&inputs(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12])).unwrap(),
+            field("2501997477381648492950318384533644783248002172679259592360114615426357826485")
+        );
+        assert_eq!(
+            hash(&inputs(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14])).unwrap(),
+            field("8354478399926161176778659061636406690034081872658507739535256090879947077494")
+        );
+        assert_eq!(
+            hash(&inputs(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16])).unwrap(),
+            field("9989051620750914585850546081941653841776809718687451684622678807385399211877")
+        );
+    }
+
+    #[test]
+    fn test_invalid_width() {
+        assert_eq!(hash(&[]), Err(Error::InvalidLength));
+        assert_eq!(hash(&inputs(&[0; 17])), Err(Error::InvalidLength));
+        assert_eq!(permute(&mut inputs(&[1])), Err(Error::InvalidLength));
+    }
+}
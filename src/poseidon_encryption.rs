@@ -0,0 +1,206 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::key::EDDSA;
+use crate::poseidon;
+use crate::prime_field::{Error, PrimeField, BN_0, BN_1};
+use crate::BabyJubjub;
+use num_bigint::BigInt;
+use sp_std::vec::Vec;
+
+// Poseidon duplex-sponge encryption as in zk-kit `poseidon-cipher` / maci
+// `poseidonEncrypt` (after Dusk's poseidon-encryption). The state is
+// [0, key.x, key.y, nonce + length * 2^128] over a width 4 permutation; the
+// message is absorbed three elements at a time and one extra element is
+// squeezed at the end as the authentication tag.
+
+// ECDH key shared between `secret_key` and the owner of `public_key`.
+pub fn shared_key(secret_key: &[u8], public_key: &BabyJubjubPoint) -> BabyJubjubPoint {
+    public_key * BabyJubjub::secret_scalar(secret_key)
+}
+
+fn initial_state(
+    key: &BabyJubjubPoint,
+    nonce: &BigInt,
+    length: usize,
+) -> Result<[BabyJubjubField; 4], Error> {
+    let two_128: BigInt = &*BN_1 << 128u32;
+    if nonce < &BN_0 || nonce >= &two_128 {
+        return Err(Error::InvalidNonce);
+    }
+
+    Ok([
+        BabyJubjubField::new(&BN_0),
+        key.x.clone(),
+        key.y.clone(),
+        BabyJubjubField::new(&(nonce + BigInt::from(length) * two_128)),
+    ])
+}
+
+// Returns 3 * ceil(len / 3) + 1 elements.
+pub fn encrypt(
+    message: &[BabyJubjubField],
+    key: &BabyJubjubPoint,
+    nonce: &BigInt,
+) -> Result<Vec<BabyJubjubField>, Error> {
+    let mut state = initial_state(key, nonce, message.len())?;
+    let zero = BabyJubjubField::new(&BN_0);
+
+    let mut ciphertext = Vec::with_capacity(message.len() + 4);
+    for chunk in message.chunks(3) {
+        poseidon::permute(&mut state)?;
+        for i in 0..3 {
+            state[i + 1] = &state[i + 1] + chunk.get(i).unwrap_or(&zero);
+            ciphertext.push(state[i + 1].clone());
+        }
+    }
+
+    poseidon::permute(&mut state)?;
+    ciphertext.push(state[1].clone());
+    Ok(ciphertext)
+}
+
+pub fn decrypt(
+    ciphertext: &[BabyJubjubField],
+    key: &BabyJubjubPoint,
+    nonce: &BigInt,
+    length: usize,
+) -> Result<Vec<BabyJubjubField>, Error> {
+    if ciphertext.len() != length.div_ceil(3) * 3 + 1 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut state = initial_state(key, nonce, length)?;
+    let (body, tag) = ciphertext.split_at(ciphertext.len() - 1);
+
+    let mut message = Vec::with_capacity(body.len());
+    for chunk in body.chunks(3) {
+        poseidon::permute(&mut state)?;
+        for i in 0..3 {
+            message.push(&chunk[i] - &state[i + 1]);
+            state[i + 1] = chunk[i].clone();
+        }
+    }
+
+    poseidon::permute(&mut state)?;
+    if state[1] != tag[0] {
+        return Err(Error::DecryptionFailed);
+    }
+
+    // the padding must decrypt to zero
+    if message[length..].iter().any(|m| m.v != *BN_0) {
+        return Err(Error::DecryptionFailed);
+    }
+
+    message.truncate(length);
+    Ok(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(n: &[u64]) -> Vec<BabyJubjubField> {
+        n.iter().map(|i| BabyJubjubField::new(&BigInt::from(*i))).collect()
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let alice = [1u8; 32];
+        let bob = [2u8; 32];
+        let key = shared_key(&alice, &BabyJubjub::pubkey_from_secretkey(&bob));
+        assert_eq!(key, shared_key(&bob, &BabyJubjub::pubkey_from_secretkey(&alice)));
+
+        let nonce = BigInt::from(5u64);
+        for len in 1..8 {
+            let m = message(&(0..len).map(|i| i * 1000 + 7).collect::<Vec<_>>());
+            let c = encrypt(&m, &key, &nonce).unwrap();
+            assert_eq!(c.len(), (len as usize).div_ceil(3) * 3 + 1);
+            assert_eq!(decrypt(&c, &key, &nonce, m.len()).unwrap(), m);
+        }
+    }
+
+    #[test]
+    fn test_decrypt_failure() {
+        let key = shared_key(&[1u8; 32], &BabyJubjub::pubkey_from_secretkey(&[2u8; 32]));
+        let other = shared_key(&[3u8; 32], &BabyJubjub::pubkey_from_secretkey(&[2u8; 32]));
+        let nonce = BigInt::from(5u64);
+        let m = message(&[1, 2, 3, 4]);
+        let c = encrypt(&m, &key, &nonce).unwrap();
+
+        assert_eq!(decrypt(&c, &other, &nonce, 4), Err(Error::DecryptionFailed));
+        assert_eq!(decrypt(&c, &key, &BigInt::from(6u64), 4), Err(Error::DecryptionFailed));
+        assert_eq!(decrypt(&c, &key, &nonce, 5), Err(Error::DecryptionFailed));
+        assert_eq!(decrypt(&c, &key, &nonce, 3), Err(Error::InvalidLength));
+
+        let mut tampered = c.clone();
+        tampered[1] = &tampered[1] + &BabyJubjubField::new(&BN_1);
+        assert_eq!(decrypt(&tampered, &key, &nonce, 4), Err(Error::DecryptionFailed));
+    }
+
+    #[test]
+    fn test_nonce_range() {
+        let key = shared_key(&[1u8; 32], &BabyJubjub::pubkey_from_secretkey(&[2u8; 32]));
+        let max = (BigInt::from(1u8) << 128u32) - 1;
+        let m = message(&[42]);
+
+        let c = encrypt(&m, &key, &max).unwrap();
+        assert_eq!(decrypt(&c, &key, &max, 1).unwrap(), m);
+        assert_eq!(encrypt(&m, &key, &(max + 1)), Err(Error::InvalidNonce));
+        assert_eq!(encrypt(&m, &key, &BigInt::from(-1)), Err(Error::InvalidNonce));
+    }
+
+    fn field(s: &str) -> BabyJubjubField {
+        BabyJubjubField::new(&BigInt::parse_bytes(s.as_bytes(), 10).unwrap())
+    }
+
+    // zk-kit `poseidonEncrypt(message, key, nonce)` with key = Base8
+    #[test]
+    fn test_known_answer() {
+        let key = BabyJubjubPoint {
+            x: field("5299619240641551281634865583518297030282874472190772894086521144482721001553"),
+            y: field("16950150798460657717958625567821834550301663161624707787222815936182638968203"),
+        };
+        let vectors: [(BigInt, &[u64], &[&str]); 3] = [
+            (
+                BigInt::from(5u64),
+                &[1, 2, 3, 4],
+                &[
+                    "9896238039041802213617172231724777502514862753675275285060714676706331428637",
+                    "2478151896077085485191609462928902353888614336574827228923119956179037040122",
+                    "16325267342777558121390902792498349395104365299074098876421221152647873058045",
+                    "12354792234256902419669824724831262543683122389884392927101412386824678448385",
+                    "11383728807503773505962838307310973148077507835319289298397985635969849055852",
+                    "8236689671412918164205665469013770790704566088279891532862515887018432829800",
+                    "5018351663397648540451061544258555194598077907079529333216739941413845403576",
+                ],
+            ),
+            (
+                BigInt::from(0u64),
+                &[1, 2, 3],
+                &[
+                    "798510073818958245026497366447415336916036964473272012783414836338379557868",
+                    "537018667834918106077687442191529411211390875172215473488974437685163504766",
+                    "3798106137442458193761868141905504955051904740446360133213447293685983416155",
+                    "5212260266856244799773228672927421903025019366891272060169010306925764944301",
+                ],
+            ),
+            (
+                (BigInt::from(1u8) << 128u32) - 1,
+                &[42],
+                &[
+                    "11823476659637508130627866580925606937136944003348968227043341162275262182743",
+                    "3098290573287252166914948788611314664834372596404560978395675577102147478703",
+                    "218142541515982272811030094498970408440325891512664653768005408372886057801",
+                    "4743984367677771266710668380869808845351493204510734057346490131952856551034",
+                ],
+            ),
+        ];
+
+        for (nonce, m, expected) in vectors.iter() {
+            let m = message(m);
+            let expected: Vec<BabyJubjubField> = expected.iter().map(|c| field(c)).collect();
+            assert_eq!(encrypt(&m, &key, nonce).unwrap(), expected);
+            assert_eq!(decrypt(&expected, &key, nonce, m.len()).unwrap(), m);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use num_bigint::{BigInt, Sign, ToBigInt};
 use num_traits::{Zero, One};
+use rand_core::{CryptoRng, RngCore};
 
 mod add;
 mod sub;
@@ -33,7 +34,21 @@ fn legendre_symbol(a: &BigInt, q: &BigInt) -> i32 {
 
 #[derive(Eq, PartialEq, Clone, Debug, PartialOrd)]
 pub enum Error {
-    NotASqure
+    NotASqure,
+    InvalidLength,
+    InvalidPoint,
+    DecryptionFailed,
+    InvalidNonce,
+    InvalidScalar,
+    InvalidShare,
+    InvalidProof,
+    InvalidSignature,
+    KeyAlreadyExists,
+    KeyNotFound,
+    MaxDepthReached,
+    NodeNotFound,
+    TreeFull,
+    InvalidTypedData,
 }
 
 pub trait PrimeField:
@@ -173,23 +188,39 @@ impl Encode for Field {
     }
 }
 
-#[cfg(feature="std")]
-use num_bigint::RandBigInt;
-
-#[cfg(feature="std")]
-pub trait Random {
-    fn get_random(l: &BigInt, r: &BigInt) -> Self;
+// Uniform in [l, r): the difference is sampled from its bit length and
+// rejected when too large, which takes under two tries on average and has no
+// modulo bias.
+pub fn random_range<R: RngCore + ?Sized>(rng: &mut R, l: &BigInt, r: &BigInt) -> BigInt {
+    let range = r - l;
+    assert!(range > BigInt::zero(), "empty range");
+    let bits = range.bits();
+    let mut bytes = sp_std::vec![0u8; bits.div_ceil(8) as usize];
+    let mask = 0xffu8 >> (bytes.len() as u64 * 8 - bits);
+    loop {
+        rng.fill_bytes(&mut bytes);
+        bytes[0] &= mask;
+        let v = BigInt::from_bytes_be(Sign::Plus, &bytes);
+        if v < range {
+            return l + v;
+        }
+    }
 }
 
-
-#[cfg(feature="std")]
-impl Random for Field {
+pub trait Random: Sized {
     // [l, r)
+    fn random_with<R: RngCore + CryptoRng + ?Sized>(rng: &mut R, l: &BigInt, r: &BigInt) -> Self;
+
+    #[cfg(feature="std")]
     fn get_random(l: &BigInt, r: &BigInt) -> Self {
-        let mut rng = rand::thread_rng();
+        Self::random_with(&mut rand::thread_rng(), l, r)
+    }
+}
 
+impl Random for Field {
+    fn random_with<R: RngCore + CryptoRng + ?Sized>(rng: &mut R, l: &BigInt, r: &BigInt) -> Self {
         Self {
-            v: rng.gen_bigint_range(l, r),
+            v: random_range(rng, l, r),
         }
     }
 }
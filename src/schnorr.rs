@@ -0,0 +1,206 @@
+use crate::babyjubjub::{random_scalar, BabyJubjubField};
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::poseidon;
+use crate::prime_field::{modulus, Encode, Error, Order, PrimeField, BN_0};
+use num_bigint::{BigInt, Sign};
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use sp_std::vec::Vec;
+
+// Plain Schnorr signatures s * G = R + e * A over BabyJubjub, with G = BASEPOINT
+// and e = H(R, A, m) mod l. Unlike the EdDSA path (`Sign`, `BabyJubjub::sign`)
+// the nonce is random or supplied by the caller, the challenge is not
+// multiplied by the cofactor and the challenge hash is a type parameter.
+
+pub trait ChallengeHash {
+    type Message: ?Sized;
+
+    fn challenge(r: &BabyJubjubPoint, public_key: &BabyJubjubPoint, msg: &Self::Message) -> BigInt;
+
+    // Byte encoding of a message, for protocols that bind it into other hashes.
+    fn message_bytes(msg: &Self::Message) -> Vec<u8>;
+}
+
+// e = SHA-256(DOMAIN || R || A || m) read as little endian, for byte messages.
+pub struct Sha256Challenge;
+
+// e = Poseidon(R.x, R.y, A.x, A.y, m), for a single field element message, as
+// computed in circuits.
+pub struct PoseidonChallenge;
+
+const SHA256_DOMAIN: &[u8] = b"delphinus-crypto/schnorr";
+
+impl ChallengeHash for Sha256Challenge {
+    type Message = [u8];
+
+    fn challenge(r: &BabyJubjubPoint, public_key: &BabyJubjubPoint, msg: &[u8]) -> BigInt {
+        let h = Sha256::new()
+            .chain(SHA256_DOMAIN)
+            .chain(r.encode())
+            .chain(public_key.encode())
+            .chain(msg)
+            .finalize();
+        BigInt::from_bytes_le(Sign::Plus, &h) % BabyJubjubField::suborder()
+    }
+
+    fn message_bytes(msg: &[u8]) -> Vec<u8> {
+        msg.to_vec()
+    }
+}
+
+impl ChallengeHash for PoseidonChallenge {
+    type Message = BabyJubjubField;
+
+    fn challenge(r: &BabyJubjubPoint, public_key: &BabyJubjubPoint, msg: &BabyJubjubField) -> BigInt {
+        let inputs = [
+            r.x.clone(),
+            r.y.clone(),
+            public_key.x.clone(),
+            public_key.y.clone(),
+            msg.clone(),
+        ];
+        poseidon::hash(&inputs).unwrap().v % BabyJubjubField::suborder()
+    }
+
+    fn message_bytes(msg: &BabyJubjubField) -> Vec<u8> {
+        msg.encode().to_vec()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchnorrSignature {
+    pub r: BabyJubjubPoint,
+    pub s: BigInt,
+}
+
+impl SchnorrSignature {
+    pub fn encode(&self) -> [u8; 64] {
+        let mut encode = [0u8; 64];
+        encode[..32].copy_from_slice(&self.r.encode());
+        encode[32..].copy_from_slice(&BabyJubjubField::new(&self.s).encode());
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 64 {
+            return Err(Error::InvalidLength);
+        }
+        let s = BabyJubjubField::decode(&encode[32..]).v;
+        if &s >= BabyJubjubField::suborder() {
+            return Err(Error::InvalidScalar);
+        }
+        Ok(SchnorrSignature {
+            r: BabyJubjubPoint::decode(&encode[..32])?,
+            s,
+        })
+    }
+}
+
+pub fn public_key(secret: &BigInt) -> BabyJubjubPoint {
+    BabyJubjubPoint::get_basepoint() * modulus(secret, BabyJubjubField::suborder())
+}
+
+// The nonce must never be reused with the same key for different messages.
+pub fn sign_with_nonce<H: ChallengeHash>(
+    msg: &H::Message,
+    secret: &BigInt,
+    nonce: &BigInt,
+) -> SchnorrSignature {
+    let l = BabyJubjubField::suborder();
+    let secret = modulus(secret, l);
+    let nonce = modulus(nonce, l);
+
+    let r = BabyJubjubPoint::get_basepoint() * &nonce;
+    let e = H::challenge(&r, &public_key(&secret), msg);
+    let s = (nonce + e * secret) % l;
+    SchnorrSignature { r, s }
+}
+
+pub fn sign_with_rng<H: ChallengeHash, R: RngCore + CryptoRng + ?Sized>(
+    msg: &H::Message,
+    secret: &BigInt,
+    rng: &mut R,
+) -> SchnorrSignature {
+    sign_with_nonce::<H>(msg, secret, &random_scalar(rng).v)
+}
+
+#[cfg(feature = "std")]
+pub fn sign<H: ChallengeHash>(msg: &H::Message, secret: &BigInt) -> SchnorrSignature {
+    sign_with_rng::<H, _>(msg, secret, &mut rand::thread_rng())
+}
+
+pub fn verify<H: ChallengeHash>(
+    msg: &H::Message,
+    signature: &SchnorrSignature,
+    public_key: &BabyJubjubPoint,
+) -> bool {
+    if signature.s < *BN_0 || &signature.s >= BabyJubjubField::suborder() {
+        return false;
+    }
+
+    let e = H::challenge(&signature.r, public_key, msg);
+    BabyJubjubPoint::get_basepoint() * &signature.s == &signature.r + public_key * e
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BabyJubjub, EDDSA};
+
+    #[test]
+    fn test_sha256_sign_verify() {
+        let secret = BigInt::from(123456789u64);
+        let pk = public_key(&secret);
+
+        let sig = sign_with_nonce::<Sha256Challenge>(b"hello", &secret, &BigInt::from(42u64));
+        assert!(verify::<Sha256Challenge>(b"hello", &sig, &pk));
+        assert!(!verify::<Sha256Challenge>(b"hellp", &sig, &pk));
+        assert!(!verify::<Sha256Challenge>(b"hello", &sig, &public_key(&BigInt::from(2u64))));
+        assert_eq!(sig, sign_with_nonce::<Sha256Challenge>(b"hello", &secret, &BigInt::from(42u64)));
+    }
+
+    #[test]
+    fn test_poseidon_sign_verify() {
+        let secret = BabyJubjub::secret_scalar(&[5u8; 32]);
+        let pk = BabyJubjub::pubkey_from_secretkey(&[5u8; 32]);
+        let m = BabyJubjubField::new(&BigInt::from(1234u64));
+
+        let sig = sign_with_nonce::<PoseidonChallenge>(&m, &secret, &BigInt::from(77u64));
+        assert!(verify::<PoseidonChallenge>(&m, &sig, &pk));
+        assert!(!verify::<PoseidonChallenge>(&BabyJubjubField::new(&BigInt::from(1235u64)), &sig, &pk));
+
+        let mut bad = sig.clone();
+        bad.s = &bad.s + BabyJubjubField::suborder();
+        assert!(!verify::<PoseidonChallenge>(&m, &bad, &pk));
+    }
+
+    #[test]
+    fn test_encode() {
+        let secret = BigInt::from(99u64);
+        let sig = sign_with_nonce::<Sha256Challenge>(b"msg", &secret, &BigInt::from(7u64));
+        assert_eq!(SchnorrSignature::decode(&sig.encode()).unwrap(), sig);
+
+        let mut encode = sig.encode();
+        encode[32..].copy_from_slice(&BabyJubjubField::new(BabyJubjubField::suborder()).encode());
+        assert_eq!(SchnorrSignature::decode(&encode), Err(Error::InvalidScalar));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_random_nonce() {
+        let secret = BigInt::from(31337u64);
+        let pk = public_key(&secret);
+        let a = sign::<Sha256Challenge>(b"msg", &secret);
+        let b = sign::<Sha256Challenge>(b"msg", &secret);
+        assert_ne!(a, b);
+        assert!(verify::<Sha256Challenge>(b"msg", &a, &pk));
+        assert!(verify::<Sha256Challenge>(b"msg", &b, &pk));
+
+        use rand::{rngs::StdRng, SeedableRng};
+        let a = sign_with_rng::<Sha256Challenge, _>(b"msg", &secret, &mut StdRng::seed_from_u64(1));
+        let b = sign_with_rng::<Sha256Challenge, _>(b"msg", &secret, &mut StdRng::seed_from_u64(1));
+        assert_eq!(a, b);
+        assert!(verify::<Sha256Challenge>(b"msg", &a, &pk));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::key::{PublicKey, SecretKey, Sign};
+use crate::prime_field::{Encode, Order};
+use alloc::string::{String, ToString};
+use num_bigint::BigInt;
+use rustc_hex::{FromHex, ToHex};
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple, Serializer};
+use sp_std::fmt;
+use sp_std::vec::Vec;
+
+// serde support for the key and signature types.
+//
+// Human readable formats such as JSON get strings: field elements in decimal,
+// as circom and snarkjs write them (0x prefixed hex is also read), points and
+// public keys as the hex of their 32 byte encoding and signatures as the hex of
+// the 64 byte R || S. Binary formats get those encodings as fixed size arrays.
+// Deserializing rejects non canonical field elements, points off the curve and
+// signatures with S not below the suborder.
+//
+// A `SecretKey` serializes as "[redacted]" so that it does not end up in logs
+// or API responses by accident. Fields that must carry the key opt in with
+// `#[serde(with = "delphinus_crypto::serialization::secret_key")]`, which
+// writes it like a public key. Both read the exposed form.
+
+pub const REDACTED: &str = "[redacted]";
+
+fn serialize_encoded<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        let hex: String = bytes.to_hex();
+        serializer.serialize_str(&hex)
+    } else {
+        let mut tuple = serializer.serialize_tuple(bytes.len())?;
+        for byte in bytes {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+}
+
+struct ArrayVisitor<const N: usize>;
+
+impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes", N)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; N], A::Error> {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        Ok(bytes)
+    }
+}
+
+fn from_hex<E: de::Error>(s: &str) -> Result<Vec<u8>, E> {
+    s.strip_prefix("0x")
+        .unwrap_or(s)
+        .from_hex()
+        .map_err(|_| E::invalid_value(de::Unexpected::Str(s), &"a hex string"))
+}
+
+fn deserialize_encoded<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+    if deserializer.is_human_readable() {
+        let s = String::deserialize(deserializer)?;
+        if s == REDACTED {
+            return Err(de::Error::custom("secret key is redacted"));
+        }
+        let bytes: Vec<u8> = from_hex(&s)?;
+        if bytes.len() != N {
+            return Err(de::Error::invalid_length(bytes.len(), &ArrayVisitor::<N>));
+        }
+        let mut out = [0u8; N];
+        out.copy_from_slice(&bytes);
+        Ok(out)
+    } else {
+        deserializer.deserialize_tuple(N, ArrayVisitor::<N>)
+    }
+}
+
+fn canonical<E: de::Error>(v: BigInt) -> Result<BabyJubjubField, E> {
+    if v >= *BabyJubjubField::order() || v < BigInt::from(0) {
+        return Err(E::custom("field element out of range"));
+    }
+    Ok(BabyJubjubField { v })
+}
+
+fn decode_point<E: de::Error>(bytes: &[u8; 32]) -> Result<BabyJubjubPoint, E> {
+    let mut y = *bytes;
+    y[31] &= 0x7f;
+    canonical::<E>(BabyJubjubField::decode(&y).v)?;
+    BabyJubjubPoint::decode(bytes).map_err(|_| E::custom("invalid point"))
+}
+
+impl Serialize for BabyJubjubField {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.v.to_string())
+        } else {
+            serialize_encoded(&self.encode(), serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for BabyJubjubField {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            let v = match s.strip_prefix("0x") {
+                Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+                None => BigInt::parse_bytes(s.as_bytes(), 10),
+            };
+            let v = v.ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&s), &"a decimal or hex number"))?;
+            canonical(v)
+        } else {
+            let bytes: [u8; 32] = deserialize_encoded(deserializer)?;
+            canonical(BabyJubjubField::decode(&bytes).v)
+        }
+    }
+}
+
+impl Serialize for BabyJubjubPoint {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_encoded(&self.encode(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BabyJubjubPoint {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        decode_point(&deserialize_encoded(deserializer)?)
+    }
+}
+
+impl Serialize for Sign<BabyJubjubField> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_encoded(&[self.r.encode(), self.s.encode()].concat(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sign<BabyJubjubField> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes: [u8; 64] = deserialize_encoded(deserializer)?;
+        let mut r = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        let s = BabyJubjubField::decode(&bytes[32..]);
+        if s.v >= *BabyJubjubField::suborder() {
+            return Err(de::Error::custom("invalid signature"));
+        }
+        Ok(Sign { r: decode_point(&r)?, s })
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(PublicKey(BabyJubjubPoint::deserialize(deserializer)?))
+    }
+}
+
+impl Serialize for SecretKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl<'de> Deserialize<'de> for SecretKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        secret_key::deserialize(deserializer)
+    }
+}
+
+pub mod secret_key {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(secret_key: &SecretKey, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_encoded(secret_key.as_bytes(), serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SecretKey, D::Error> {
+        Ok(SecretKey::from_bytes(deserialize_encoded(deserializer)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::EDDSA;
+    use crate::prime_field::PrimeField;
+    use crate::BabyJubjub;
+    use serde_json::json;
+
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct Keypair {
+        #[serde(with = "secret_key")]
+        secret: SecretKey,
+        public: PublicKey,
+    }
+
+    #[test]
+    fn test_field() {
+        let a = BabyJubjubField::new(&BigInt::from(1234u64));
+        assert_eq!(serde_json::to_value(&a).unwrap(), json!("1234"));
+        assert_eq!(serde_json::from_value::<BabyJubjubField>(json!("1234")).unwrap(), a);
+        assert_eq!(serde_json::from_value::<BabyJubjubField>(json!("0x4d2")).unwrap(), a);
+
+        let order = BabyJubjubField::order().to_string();
+        assert!(serde_json::from_value::<BabyJubjubField>(json!(order)).is_err());
+        assert!(serde_json::from_value::<BabyJubjubField>(json!("-1")).is_err());
+        assert!(serde_json::from_value::<BabyJubjubField>(json!("12a")).is_err());
+    }
+
+    #[test]
+    fn test_point_and_signature() {
+        let secret_key = SecretKey::from_bytes([3u8; 32]);
+        let public_key = secret_key.public_key();
+        let sign = BabyJubjub::sign(b"m", secret_key.as_bytes());
+
+        let json = serde_json::to_value(&public_key).unwrap();
+        let hex: String = public_key.encode().to_hex();
+        assert_eq!(json, json!(hex));
+        assert_eq!(serde_json::from_value::<PublicKey>(json).unwrap(), public_key);
+        assert_eq!(
+            serde_json::from_value::<BabyJubjubPoint>(serde_json::to_value(&public_key.0).unwrap()).unwrap(),
+            public_key.0
+        );
+
+        let encoded = [sign.r.encode(), sign.s.encode()].concat();
+        let json = serde_json::to_value(&sign).unwrap();
+        let hex: String = encoded.to_hex();
+        assert_eq!(json, json!(hex));
+        let decoded: Sign<BabyJubjubField> = serde_json::from_value(json).unwrap();
+        assert!(BabyJubjub::verify(b"m", decoded, public_key.0.clone()));
+
+        let mut bad = encoded.clone();
+        bad[63] = 0xff;
+        let hex: String = bad.to_hex();
+        assert!(serde_json::from_value::<Sign<BabyJubjubField>>(json!(hex)).is_err());
+        let hex: String = encoded[1..].to_hex();
+        assert!(serde_json::from_value::<Sign<BabyJubjubField>>(json!(hex)).is_err());
+        assert!(serde_json::from_value::<PublicKey>(json!("zz")).is_err());
+    }
+
+    #[test]
+    fn test_secret_key() {
+        let secret_key = SecretKey::from_bytes([3u8; 32]);
+        assert_eq!(serde_json::to_value(&secret_key).unwrap(), json!(REDACTED));
+        assert!(serde_json::from_value::<SecretKey>(json!(REDACTED)).is_err());
+
+        let keypair = Keypair {
+            public: secret_key.public_key(),
+            secret: secret_key,
+        };
+        let json = serde_json::to_value(&keypair).unwrap();
+        let hex: String = [3u8; 32].to_hex();
+        assert_eq!(json["secret"], json!(hex));
+        let decoded: Keypair = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.secret.as_bytes(), &[3u8; 32]);
+        assert_eq!(decoded.public, decoded.secret.public_key());
+    }
+}
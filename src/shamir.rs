@@ -0,0 +1,484 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::commitment::generator_h;
+use crate::curve::Curve;
+use crate::prime_field::{modulus, random_range, Encode, Error, Order, PrimeField, BN_0, BN_1, BN_2};
+use num_bigint::{BigInt, Sign};
+use rand_core::{CryptoRng, RngCore};
+use sp_std::collections::btree_set::BTreeSet;
+use sp_std::vec::Vec;
+
+// Shamir secret sharing over the scalar field of the prime order subgroup,
+// with Feldman commitments C_k = a_k * BASEPOINT to the coefficients of the
+// sharing polynomial f(x) = a_0 + a_1 * x + ... + a_{t-1} * x^{t-1}. Share i is
+// (i, f(i)) and is valid iff f(i) * BASEPOINT == sum_k C_k * i^k. Any
+// `threshold` shares recover a_0 by Lagrange interpolation at 0.
+//
+// When the secret is a signing scalar, C_0 is its public key, so shares can be
+// checked against the known key. `split_secret_key` shares the 32 bytes of an
+// EdDSA secret key instead, as two 128-bit limbs, so that the recovered key
+// still signs with `BabyJubjub::sign`. Feldman commitments would expose each
+// limb to a 2^64 discrete log, so the limbs use Pedersen VSS: a second random
+// polynomial g blinds the commitments C_k = a_k * BASEPOINT + b_k * H, and
+// share i is valid iff f(i) * BASEPOINT + g(i) * H == sum_k C_k * i^k.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Share {
+    pub identifier: u16,
+    pub value: BigInt,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Commitments(pub Vec<BabyJubjubPoint>);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecretKeyShare {
+    pub identifier: u16,
+    pub limbs: [BigInt; 2],
+    pub blindings: [BigInt; 2],
+}
+
+fn encode_scalar(s: &BigInt) -> [u8; 32] {
+    BabyJubjubField::new(s).encode()
+}
+
+fn decode_scalar(encode: &[u8]) -> Result<BigInt, Error> {
+    let s = BigInt::from_bytes_le(Sign::Plus, encode);
+    if &s >= BabyJubjubField::suborder() {
+        return Err(Error::InvalidScalar);
+    }
+    Ok(s)
+}
+
+fn decode_identifier(encode: &[u8]) -> Result<u16, Error> {
+    match u16::from_le_bytes([encode[0], encode[1]]) {
+        0 => Err(Error::InvalidScalar),
+        identifier => Ok(identifier),
+    }
+}
+
+impl Share {
+    // identifier as 2 bytes little endian || value
+    pub fn encode(&self) -> [u8; 34] {
+        let mut encode = [0u8; 34];
+        encode[..2].copy_from_slice(&self.identifier.to_le_bytes());
+        encode[2..].copy_from_slice(&encode_scalar(&self.value));
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 34 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Share {
+            identifier: decode_identifier(encode)?,
+            value: decode_scalar(&encode[2..])?,
+        })
+    }
+}
+
+impl Commitments {
+    pub fn threshold(&self) -> usize {
+        self.0.len()
+    }
+
+    // C_0, the public key of the shared secret
+    pub fn public_key(&self) -> &BabyJubjubPoint {
+        &self.0[0]
+    }
+
+    // sum_k C_k * x^k, the public image of f(x)
+    pub fn evaluate(&self, x: u16) -> BabyJubjubPoint {
+        let l = BabyJubjubField::suborder();
+        let x = BigInt::from(x);
+        let mut power = BN_1.clone();
+        let mut acc = BabyJubjubPoint::get_origin().clone();
+        for c in self.0.iter() {
+            acc = acc + c * &power;
+            power = (power * &x) % l;
+        }
+        acc
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.0.iter().flat_map(|c| c.encode().to_vec()).collect()
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        let chunks = encode.chunks_exact(32);
+        if encode.is_empty() || !chunks.remainder().is_empty() {
+            return Err(Error::InvalidLength);
+        }
+        let commitments = chunks
+            .map(BabyJubjubPoint::decode)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Commitments(commitments))
+    }
+}
+
+impl SecretKeyShare {
+    // identifier as 2 bytes little endian || low limb || high limb ||
+    // low blinding || high blinding
+    pub fn encode(&self) -> [u8; 130] {
+        let mut encode = [0u8; 130];
+        encode[..2].copy_from_slice(&self.identifier.to_le_bytes());
+        for (i, s) in self.limbs.iter().chain(self.blindings.iter()).enumerate() {
+            encode[2 + 32 * i..34 + 32 * i].copy_from_slice(&encode_scalar(s));
+        }
+        encode
+    }
+
+    pub fn decode(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 130 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(SecretKeyShare {
+            identifier: decode_identifier(encode)?,
+            limbs: [decode_scalar(&encode[2..34])?, decode_scalar(&encode[34..66])?],
+            blindings: [decode_scalar(&encode[66..98])?, decode_scalar(&encode[98..])?],
+        })
+    }
+
+    fn limb(&self, i: usize) -> Share {
+        Share {
+            identifier: self.identifier,
+            value: self.limbs[i].clone(),
+        }
+    }
+}
+
+pub fn evaluate_polynomial(coefficients: &[BigInt], x: u16) -> BigInt {
+    let l = BabyJubjubField::suborder();
+    let x = BigInt::from(x);
+    coefficients
+        .iter()
+        .rev()
+        .fold(BN_0.clone(), |acc, c| (acc * &x + c) % l)
+}
+
+fn scalar_inv(a: &BigInt) -> BigInt {
+    let l = BabyJubjubField::suborder();
+    modulus(a, l).modpow(&(l - &*BN_2), l)
+}
+
+// lambda_i = prod_{j != i} j / (j - i), the coefficient of f(i) in f(0)
+pub fn lagrange_coefficient(identifier: u16, identifiers: &[u16]) -> BigInt {
+    let l = BabyJubjubField::suborder();
+    let i = BigInt::from(identifier);
+    let (num, den) = identifiers
+        .iter()
+        .filter(|j| **j != identifier)
+        .fold((BN_1.clone(), BN_1.clone()), |(num, den), j| {
+            let j = BigInt::from(*j);
+            ((num * &j) % l, (den * modulus(&(&j - &i), l)) % l)
+        });
+    (num * scalar_inv(&den)) % l
+}
+
+// `coefficients` are a_1, ..., a_{t-1}, uniformly random and secret.
+pub fn split_with(
+    secret: &BigInt,
+    threshold: u16,
+    shares: u16,
+    coefficients: &[BigInt],
+) -> Result<(Vec<Share>, Commitments), Error> {
+    if threshold < 1 || shares < threshold || coefficients.len() + 1 != threshold as usize {
+        return Err(Error::InvalidLength);
+    }
+
+    let l = BabyJubjubField::suborder();
+    let polynomial: Vec<BigInt> = sp_std::iter::once(secret)
+        .chain(coefficients.iter())
+        .map(|a| modulus(a, l))
+        .collect();
+    let commitments = Commitments(
+        polynomial
+            .iter()
+            .map(|a| BabyJubjubPoint::get_basepoint() * a)
+            .collect(),
+    );
+    let shares = (1..=shares)
+        .map(|identifier| Share {
+            identifier,
+            value: evaluate_polynomial(&polynomial, identifier),
+        })
+        .collect();
+    Ok((shares, commitments))
+}
+
+pub fn split_with_rng<R: RngCore + CryptoRng + ?Sized>(
+    secret: &BigInt,
+    threshold: u16,
+    shares: u16,
+    rng: &mut R,
+) -> Result<(Vec<Share>, Commitments), Error> {
+    let coefficients: Vec<BigInt> = (1..threshold)
+        .map(|_| random_range(rng, &BN_0, BabyJubjubField::suborder()))
+        .collect();
+    split_with(secret, threshold, shares, &coefficients)
+}
+
+#[cfg(feature = "std")]
+pub fn split(secret: &BigInt, threshold: u16, shares: u16) -> Result<(Vec<Share>, Commitments), Error> {
+    split_with_rng(secret, threshold, shares, &mut rand::thread_rng())
+}
+
+// Interpolates f(0) from the given shares. The caller must supply at least
+// `threshold` shares, fewer give an unrelated value.
+pub fn reconstruct(shares: &[Share]) -> Result<BigInt, Error> {
+    let identifiers: Vec<u16> = shares.iter().map(|s| s.identifier).collect();
+    let distinct: BTreeSet<u16> = identifiers.iter().cloned().collect();
+    if shares.is_empty() || distinct.len() != shares.len() {
+        return Err(Error::InvalidLength);
+    }
+    if distinct.contains(&0) {
+        return Err(Error::InvalidScalar);
+    }
+
+    let l = BabyJubjubField::suborder();
+    Ok(shares.iter().fold(BN_0.clone(), |acc, s| {
+        (acc + lagrange_coefficient(s.identifier, &identifiers) * &s.value) % l
+    }))
+}
+
+pub fn verify_share(share: &Share, commitments: &Commitments) -> bool {
+    if share.identifier == 0 || share.value < *BN_0 || &share.value >= BabyJubjubField::suborder() {
+        return false;
+    }
+    BabyJubjubPoint::get_basepoint() * &share.value == commitments.evaluate(share.identifier)
+}
+
+fn secret_key_limbs(secret_key: &[u8]) -> Result<[BigInt; 2], Error> {
+    if secret_key.len() != 32 {
+        return Err(Error::InvalidLength);
+    }
+    Ok([
+        BigInt::from_bytes_le(Sign::Plus, &secret_key[..16]),
+        BigInt::from_bytes_le(Sign::Plus, &secret_key[16..]),
+    ])
+}
+
+// Pedersen VSS of `secret`, `blinding` is b_0, ..., b_{t-1}. Returns the
+// shares of f and g.
+fn split_hiding(
+    secret: &BigInt,
+    threshold: u16,
+    shares: u16,
+    coefficients: &[BigInt],
+    blinding: &[BigInt],
+) -> Result<(Vec<Share>, Vec<Share>, Commitments), Error> {
+    if threshold < 1 || blinding.len() != threshold as usize {
+        return Err(Error::InvalidLength);
+    }
+    let (values, feldman) = split_with(secret, threshold, shares, coefficients)?;
+    let (blindings, _) = split_with(&blinding[0], threshold, shares, &blinding[1..])?;
+
+    let l = BabyJubjubField::suborder();
+    let commitments = feldman
+        .0
+        .into_iter()
+        .zip(blinding.iter())
+        .map(|(c, b)| c + generator_h() * &modulus(b, l))
+        .collect();
+    Ok((values, blindings, Commitments(commitments)))
+}
+
+fn verify_hiding(share: &Share, blinding: &BigInt, commitments: &Commitments) -> bool {
+    if share.identifier == 0
+        || share.value < *BN_0
+        || &share.value >= BabyJubjubField::suborder()
+        || blinding < &*BN_0
+        || blinding >= BabyJubjubField::suborder()
+    {
+        return false;
+    }
+    BabyJubjubPoint::get_basepoint() * &share.value + generator_h() * blinding
+        == commitments.evaluate(share.identifier)
+}
+
+// `coefficients` are the random a_1, ..., a_{t-1} and `blindings` the random
+// b_0, ..., b_{t-1} of the low and the high limb.
+pub fn split_secret_key_with(
+    secret_key: &[u8],
+    threshold: u16,
+    shares: u16,
+    coefficients: &[Vec<BigInt>; 2],
+    blindings: &[Vec<BigInt>; 2],
+) -> Result<(Vec<SecretKeyShare>, [Commitments; 2]), Error> {
+    let [lo, hi] = secret_key_limbs(secret_key)?;
+    let (lo_shares, lo_blindings, lo_commitments) =
+        split_hiding(&lo, threshold, shares, &coefficients[0], &blindings[0])?;
+    let (hi_shares, hi_blindings, hi_commitments) =
+        split_hiding(&hi, threshold, shares, &coefficients[1], &blindings[1])?;
+    let shares = lo_shares
+        .into_iter()
+        .zip(hi_shares)
+        .zip(lo_blindings.into_iter().zip(hi_blindings))
+        .map(|((lo, hi), (lo_blinding, hi_blinding))| SecretKeyShare {
+            identifier: lo.identifier,
+            limbs: [lo.value, hi.value],
+            blindings: [lo_blinding.value, hi_blinding.value],
+        })
+        .collect();
+    Ok((shares, [lo_commitments, hi_commitments]))
+}
+
+pub fn split_secret_key_with_rng<R: RngCore + CryptoRng + ?Sized>(
+    secret_key: &[u8],
+    threshold: u16,
+    shares: u16,
+    rng: &mut R,
+) -> Result<(Vec<SecretKeyShare>, [Commitments; 2]), Error> {
+    let mut random = |n: u16| -> Vec<BigInt> {
+        (0..n)
+            .map(|_| random_range(rng, &BN_0, BabyJubjubField::suborder()))
+            .collect()
+    };
+    let n = threshold.saturating_sub(1);
+    let coefficients = [random(n), random(n)];
+    let blindings = [random(threshold), random(threshold)];
+    split_secret_key_with(secret_key, threshold, shares, &coefficients, &blindings)
+}
+
+#[cfg(feature = "std")]
+pub fn split_secret_key(
+    secret_key: &[u8],
+    threshold: u16,
+    shares: u16,
+) -> Result<(Vec<SecretKeyShare>, [Commitments; 2]), Error> {
+    split_secret_key_with_rng(secret_key, threshold, shares, &mut rand::thread_rng())
+}
+
+pub fn reconstruct_secret_key(shares: &[SecretKeyShare]) -> Result<[u8; 32], Error> {
+    let mut secret_key = [0u8; 32];
+    for i in 0..2 {
+        let limb: Vec<Share> = shares.iter().map(|s| s.limb(i)).collect();
+        let (_, bytes) = reconstruct(&limb)?.to_bytes_le();
+        // a limb above 128 bits means too few or inconsistent shares
+        if bytes.len() > 16 {
+            return Err(Error::InvalidShare);
+        }
+        secret_key[16 * i..16 * i + bytes.len()].copy_from_slice(&bytes);
+    }
+    Ok(secret_key)
+}
+
+pub fn verify_secret_key_share(share: &SecretKeyShare, commitments: &[Commitments; 2]) -> bool {
+    (0..2).all(|i| verify_hiding(&share.limb(i), &share.blindings[i], &commitments[i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BabyJubjub, EDDSA};
+
+    fn coefficients(seed: u64, n: u16) -> Vec<BigInt> {
+        (0..n as u64).map(|k| BigInt::from(seed * 1_000_003 + k)).collect()
+    }
+
+    #[test]
+    fn test_split_reconstruct() {
+        let secret = BabyJubjub::secret_scalar(&[9u8; 32]) % BabyJubjubField::suborder();
+        let (shares, commitments) = split_with(&secret, 3, 5, &coefficients(1, 2)).unwrap();
+
+        // C_0 is the EdDSA public key of the secret scalar
+        assert_eq!(*commitments.public_key(), BabyJubjub::pubkey_from_secretkey(&[9u8; 32]));
+
+        for share in shares.iter() {
+            assert!(verify_share(share, &commitments));
+            assert_eq!(Share::decode(&share.encode()).unwrap(), *share);
+        }
+        assert_eq!(reconstruct(&shares[..3]).unwrap(), secret);
+        assert_eq!(reconstruct(&[shares[4].clone(), shares[0].clone(), shares[2].clone()]).unwrap(), secret);
+        assert_eq!(reconstruct(&shares).unwrap(), secret);
+        assert_ne!(reconstruct(&shares[..2]).unwrap(), secret);
+
+        let mut bad = shares[1].clone();
+        bad.value += 1;
+        assert!(!verify_share(&bad, &commitments));
+        assert_eq!(Commitments::decode(&commitments.encode()).unwrap(), commitments);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(split_with(&BN_1, 3, 2, &coefficients(1, 2)).is_err());
+        assert!(split_with(&BN_1, 3, 5, &coefficients(1, 1)).is_err());
+
+        let (shares, _) = split_with(&BN_1, 2, 3, &coefficients(2, 1)).unwrap();
+        assert_eq!(reconstruct(&[]), Err(Error::InvalidLength));
+        assert_eq!(reconstruct(&[shares[0].clone(), shares[0].clone()]), Err(Error::InvalidLength));
+
+        let mut encode = shares[0].encode();
+        encode[..2].copy_from_slice(&[0, 0]);
+        assert_eq!(Share::decode(&encode), Err(Error::InvalidScalar));
+    }
+
+    #[test]
+    fn test_split_secret_key() {
+        let secret_key = [0xa5u8; 32];
+        let (shares, commitments) = split_secret_key_with(
+            &secret_key,
+            2,
+            3,
+            &[coefficients(3, 1), coefficients(4, 1)],
+            &[coefficients(5, 2), coefficients(6, 2)],
+        )
+        .unwrap();
+
+        for share in shares.iter() {
+            assert!(verify_secret_key_share(share, &commitments));
+            assert_eq!(SecretKeyShare::decode(&share.encode()).unwrap(), *share);
+        }
+
+        // the commitments do not reveal the limbs
+        let [lo, _] = secret_key_limbs(&secret_key).unwrap();
+        assert_ne!(*commitments[0].public_key(), BabyJubjubPoint::get_basepoint() * &lo);
+
+        let mut bad = shares[0].clone();
+        bad.blindings[1] += 1;
+        assert!(!verify_secret_key_share(&bad, &commitments));
+        assert!(split_secret_key_with(
+            &secret_key,
+            2,
+            3,
+            &[coefficients(3, 1), coefficients(4, 1)],
+            &[coefficients(5, 1), coefficients(6, 2)],
+        )
+        .is_err());
+
+        let recovered = reconstruct_secret_key(&shares[1..]).unwrap();
+        assert_eq!(recovered, secret_key);
+        let sig = BabyJubjub::sign(b"backup", &recovered);
+        assert!(BabyJubjub::verify(b"backup", sig, BabyJubjub::pubkey_from_secretkey(&secret_key)));
+    }
+
+    #[test]
+    fn test_lagrange_coefficient() {
+        // interpolating f(x) = 5 + 3x at 0 from f(2) and f(5)
+        let ids = [2u16, 5];
+        let f = |x: u16| BigInt::from(5 + 3 * x as u64);
+        let secret = ids
+            .iter()
+            .fold(BN_0.clone(), |acc, i| acc + lagrange_coefficient(*i, &ids) * f(*i))
+            % BabyJubjubField::suborder();
+        assert_eq!(secret, BigInt::from(5u64));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_random_split() {
+        let (shares, commitments) = split_secret_key(&[7u8; 32], 3, 4).unwrap();
+        assert!(shares.iter().all(|s| verify_secret_key_share(s, &commitments)));
+        assert_eq!(reconstruct_secret_key(&shares[1..]).unwrap(), [7u8; 32]);
+
+        use rand::{rngs::StdRng, SeedableRng};
+        let (shares, commitments) = split_with_rng(&BigInt::from(9u64), 2, 3, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(split_with_rng(&BigInt::from(9u64), 2, 3, &mut StdRng::seed_from_u64(1)).unwrap().1, commitments);
+        assert!(shares.iter().all(|s| verify_share(s, &commitments)));
+        assert_eq!(reconstruct(&shares[1..]).unwrap(), BigInt::from(9u64));
+
+        let (shares, commitments) = split_secret_key_with_rng(&[7u8; 32], 2, 3, &mut StdRng::seed_from_u64(2)).unwrap();
+        assert!(shares.iter().all(|s| verify_secret_key_share(s, &commitments)));
+        assert_eq!(reconstruct_secret_key(&shares[..2]).unwrap(), [7u8; 32]);
+    }
+}
@@ -0,0 +1,351 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::poseidon;
+use crate::prime_field::{Error, PrimeField, BN_0, BN_1};
+use num_integer::Integer;
+use sp_std::vec::Vec;
+
+mod storage;
+
+pub use self::storage::{MemoryStorage, Node, Storage};
+
+// Sparse Merkle tree over BabyJubjubField keys and values, laid out as
+// circomlib's smt.js so that proofs check with its SMTVerifier circuit:
+//
+//   leaf = Poseidon(key, value, 1), middle = Poseidon(left, right), empty = 0,
+//
+// the path of a key is its bits from the least significant one, and a leaf sits
+// at the shortest path prefix that no other key shares. A tree of n keys is
+// thus about log2(n) deep, `max_depth` bounds it for circuits with a fixed
+// number of levels.
+
+// (key, value) of a leaf
+pub type Entry = (BabyJubjubField, BabyJubjubField);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proof {
+    // membership proof if true, non-membership proof otherwise
+    pub existence: bool,
+    // from the root down
+    pub siblings: Vec<BabyJubjubField>,
+    // non-membership only: the other leaf found on the key's path, if any
+    pub node_aux: Option<Entry>,
+}
+
+pub struct SparseMerkleTree<S: Storage> {
+    storage: S,
+    root: BabyJubjubField,
+    max_depth: usize,
+}
+
+fn zero() -> BabyJubjubField {
+    BabyJubjubField::new(&BN_0)
+}
+
+fn bit(key: &BabyJubjubField, level: usize) -> bool {
+    (&key.v >> level).is_odd()
+}
+
+pub fn hash_leaf(key: &BabyJubjubField, value: &BabyJubjubField) -> BabyJubjubField {
+    poseidon::hash(&[key.clone(), value.clone(), BabyJubjubField::new(&BN_1)]).unwrap()
+}
+
+pub fn hash_middle(left: &BabyJubjubField, right: &BabyJubjubField) -> BabyJubjubField {
+    poseidon::hash(&[left.clone(), right.clone()]).unwrap()
+}
+
+fn root_from_path(key: &BabyJubjubField, leaf: BabyJubjubField, siblings: &[BabyJubjubField]) -> BabyJubjubField {
+    siblings.iter().enumerate().rev().fold(leaf, |node, (level, sibling)| {
+        if bit(key, level) {
+            hash_middle(sibling, &node)
+        } else {
+            hash_middle(&node, sibling)
+        }
+    })
+}
+
+// Checks a proof from `SparseMerkleTree::prove`. `value` is the value of the
+// key for a membership proof and None for a non-membership proof.
+pub fn verify_proof(
+    root: &BabyJubjubField,
+    key: &BabyJubjubField,
+    value: Option<&BabyJubjubField>,
+    proof: &Proof,
+) -> bool {
+    let leaf = match (proof.existence, value, &proof.node_aux) {
+        (true, Some(value), None) => hash_leaf(key, value),
+        (false, None, None) => zero(),
+        (false, None, Some((aux_key, aux_value))) => {
+            // the other leaf must sit on the key's path
+            let depth = proof.siblings.len();
+            if aux_key == key || (0..depth).any(|level| bit(aux_key, level) != bit(key, level)) {
+                return false;
+            }
+            hash_leaf(aux_key, aux_value)
+        }
+        _ => return false,
+    };
+    root_from_path(key, leaf, &proof.siblings) == *root
+}
+
+impl SparseMerkleTree<MemoryStorage> {
+    pub fn in_memory(max_depth: usize) -> Self {
+        Self::new(MemoryStorage::new(), max_depth)
+    }
+}
+
+impl<S: Storage> SparseMerkleTree<S> {
+    pub fn new(storage: S, max_depth: usize) -> Self {
+        Self::with_root(storage, zero(), max_depth)
+    }
+
+    // Opens the tree at `root`, whose nodes must be in `storage`.
+    pub fn with_root(storage: S, root: BabyJubjubField, max_depth: usize) -> Self {
+        SparseMerkleTree {
+            storage,
+            root,
+            max_depth,
+        }
+    }
+
+    pub fn root(&self) -> &BabyJubjubField {
+        &self.root
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn storage(&self) -> &S {
+        &self.storage
+    }
+
+    // Walks down the key's path, returning the siblings and the leaf the path
+    // ends at, if it does not end at an empty node.
+    fn lookup(&self, key: &BabyJubjubField) -> Result<(Vec<BabyJubjubField>, Option<Entry>), Error> {
+        let mut siblings = Vec::new();
+        let mut node = self.root.clone();
+        loop {
+            if node == zero() {
+                return Ok((siblings, None));
+            }
+            match self.storage.get(&node).ok_or(Error::NodeNotFound)? {
+                Node::Leaf { key, value } => return Ok((siblings, Some((key, value)))),
+                Node::Middle { left, right } => {
+                    if siblings.len() == self.max_depth {
+                        return Err(Error::MaxDepthReached);
+                    }
+                    if bit(key, siblings.len()) {
+                        siblings.push(left);
+                        node = right;
+                    } else {
+                        siblings.push(right);
+                        node = left;
+                    }
+                }
+            }
+        }
+    }
+
+    // Stores the nodes from `leaf` up to the new root.
+    fn update_path(&mut self, key: &BabyJubjubField, leaf: BabyJubjubField, siblings: &[BabyJubjubField]) {
+        let mut node = leaf;
+        for (level, sibling) in siblings.iter().enumerate().rev() {
+            let (left, right) = if bit(key, level) {
+                (sibling.clone(), node)
+            } else {
+                (node, sibling.clone())
+            };
+            node = hash_middle(&left, &right);
+            self.storage.put(&node, Node::Middle { left, right });
+        }
+        self.root = node;
+    }
+
+    pub fn get(&self, key: &BabyJubjubField) -> Result<Option<BabyJubjubField>, Error> {
+        match self.lookup(key)? {
+            (_, Some((k, value))) if k == *key => Ok(Some(value)),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn insert(&mut self, key: &BabyJubjubField, value: &BabyJubjubField) -> Result<(), Error> {
+        let (mut siblings, leaf) = self.lookup(key)?;
+
+        if let Some((old_key, old_value)) = leaf {
+            if old_key == *key {
+                return Err(Error::KeyAlreadyExists);
+            }
+            // push the old leaf down to where the two paths split
+            while bit(key, siblings.len()) == bit(&old_key, siblings.len()) {
+                if siblings.len() + 1 >= self.max_depth {
+                    return Err(Error::MaxDepthReached);
+                }
+                siblings.push(zero());
+            }
+            siblings.push(hash_leaf(&old_key, &old_value));
+        }
+        if siblings.len() > self.max_depth {
+            return Err(Error::MaxDepthReached);
+        }
+
+        let leaf = hash_leaf(key, value);
+        self.storage.put(&leaf, Node::Leaf { key: key.clone(), value: value.clone() });
+        self.update_path(key, leaf, &siblings);
+        Ok(())
+    }
+
+    pub fn update(&mut self, key: &BabyJubjubField, value: &BabyJubjubField) -> Result<(), Error> {
+        let siblings = match self.lookup(key)? {
+            (siblings, Some((k, _))) if k == *key => siblings,
+            _ => return Err(Error::KeyNotFound),
+        };
+
+        let leaf = hash_leaf(key, value);
+        self.storage.put(&leaf, Node::Leaf { key: key.clone(), value: value.clone() });
+        self.update_path(key, leaf, &siblings);
+        Ok(())
+    }
+
+    pub fn delete(&mut self, key: &BabyJubjubField) -> Result<(), Error> {
+        let mut siblings = match self.lookup(key)? {
+            (siblings, Some((k, _))) if k == *key => siblings,
+            _ => return Err(Error::KeyNotFound),
+        };
+
+        // a leaf left alone under its parent moves up past the empty siblings
+        let mut node = zero();
+        if let Some(sibling) = siblings.last() {
+            if let Some(Node::Leaf { .. }) = self.storage.get(sibling) {
+                node = siblings.pop().unwrap();
+                while siblings.last() == Some(&zero()) {
+                    siblings.pop();
+                }
+            }
+        }
+        self.update_path(key, node, &siblings);
+        Ok(())
+    }
+
+    // Membership proof if the key is in the tree, non-membership otherwise.
+    pub fn prove(&self, key: &BabyJubjubField) -> Result<Proof, Error> {
+        let (siblings, leaf) = self.lookup(key)?;
+        let proof = match leaf {
+            Some((k, _)) if k == *key => Proof {
+                existence: true,
+                siblings,
+                node_aux: None,
+            },
+            node_aux => Proof {
+                existence: false,
+                siblings,
+                node_aux,
+            },
+        };
+        Ok(proof)
+    }
+}
+
+impl Proof {
+    // Siblings padded with zeros to `levels`, as the circuit inputs expect.
+    pub fn padded_siblings(&self, levels: usize) -> Vec<BabyJubjubField> {
+        let mut siblings = self.siblings.clone();
+        siblings.resize(levels.max(siblings.len()), zero());
+        siblings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+
+    fn f(n: u64) -> BabyJubjubField {
+        BabyJubjubField::new(&BigInt::from(n))
+    }
+
+    #[test]
+    fn test_insert_get_proofs() {
+        let mut tree = SparseMerkleTree::in_memory(16);
+        assert_eq!(*tree.root(), zero());
+
+        for (k, v) in [(1u64, 10u64), (9, 90), (333, 3), (4, 40)].iter() {
+            tree.insert(&f(*k), &f(*v)).unwrap();
+        }
+        assert_eq!(tree.insert(&f(9), &f(1)), Err(Error::KeyAlreadyExists));
+        assert_eq!(tree.get(&f(333)).unwrap(), Some(f(3)));
+        assert_eq!(tree.get(&f(2)).unwrap(), None);
+
+        let root = tree.root().clone();
+        let proof = tree.prove(&f(9)).unwrap();
+        assert!(proof.existence);
+        assert!(verify_proof(&root, &f(9), Some(&f(90)), &proof));
+        assert!(!verify_proof(&root, &f(9), Some(&f(91)), &proof));
+        assert!(!verify_proof(&root, &f(9), None, &proof));
+
+        // 17 ends at the leaf of 1 (same low bits), 2 at an empty node
+        let proof = tree.prove(&f(17)).unwrap();
+        assert!(!proof.existence);
+        assert_eq!(proof.node_aux, Some((f(1), f(10))));
+        assert!(verify_proof(&root, &f(17), None, &proof));
+        let proof = tree.prove(&f(2)).unwrap();
+        assert!(!proof.existence);
+        assert!(verify_proof(&root, &f(2), None, &proof));
+        assert!(!verify_proof(&root, &f(4), None, &proof));
+    }
+
+    #[test]
+    fn test_update_delete() {
+        let keys = [5u64, 13, 21, 2, 1000];
+        let mut tree = SparseMerkleTree::in_memory(16);
+        let mut roots = sp_std::vec![tree.root().clone()];
+        for k in keys.iter() {
+            tree.insert(&f(*k), &f(k + 1)).unwrap();
+            roots.push(tree.root().clone());
+        }
+
+        let before = tree.root().clone();
+        tree.update(&f(21), &f(0)).unwrap();
+        assert_ne!(*tree.root(), before);
+        tree.update(&f(21), &f(22)).unwrap();
+        assert_eq!(*tree.root(), before);
+        assert_eq!(tree.update(&f(6), &f(0)), Err(Error::KeyNotFound));
+
+        // deleting in reverse order walks back through the same roots
+        for k in keys.iter().rev() {
+            roots.pop();
+            tree.delete(&f(*k)).unwrap();
+            assert_eq!(tree.root(), roots.last().unwrap());
+        }
+        assert_eq!(*tree.root(), zero());
+        assert_eq!(tree.delete(&f(5)), Err(Error::KeyNotFound));
+    }
+
+    #[test]
+    fn test_order_independence() {
+        let mut a = SparseMerkleTree::in_memory(16);
+        let mut b = SparseMerkleTree::in_memory(16);
+        for k in [3u64, 11, 7, 19].iter() {
+            a.insert(&f(*k), &f(*k)).unwrap();
+        }
+        for k in [19u64, 7, 3, 11].iter() {
+            b.insert(&f(*k), &f(*k)).unwrap();
+        }
+        assert_eq!(a.root(), b.root());
+
+        // reopening at an old root
+        let root = a.root().clone();
+        a.delete(&f(7)).unwrap();
+        let old = SparseMerkleTree::with_root(a.storage().clone(), root, 16);
+        assert_eq!(old.get(&f(7)).unwrap(), Some(f(7)));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let mut tree = SparseMerkleTree::in_memory(3);
+        tree.insert(&f(0), &f(1)).unwrap();
+        // 0 and 8 share their three lowest bits
+        assert_eq!(tree.insert(&f(8), &f(1)), Err(Error::MaxDepthReached));
+        tree.insert(&f(4), &f(1)).unwrap();
+        assert_eq!(tree.prove(&f(4)).unwrap().padded_siblings(3).len(), 3);
+    }
+}
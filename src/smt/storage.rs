@@ -0,0 +1,52 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::prime_field::Encode;
+use sp_std::collections::btree_map::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Middle {
+        left: BabyJubjubField,
+        right: BabyJubjubField,
+    },
+    Leaf {
+        key: BabyJubjubField,
+        value: BabyJubjubField,
+    },
+}
+
+// Content addressed node store: nodes are put under their hash and never
+// modified, so a backend only needs get and put. Nodes of old roots are kept,
+// which lets a tree be reopened at any earlier root.
+pub trait Storage {
+    fn get(&self, hash: &BabyJubjubField) -> Option<Node>;
+    fn put(&mut self, hash: &BabyJubjubField, node: Node);
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MemoryStorage {
+    nodes: BTreeMap<[u8; 32], Node>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, hash: &BabyJubjubField) -> Option<Node> {
+        self.nodes.get(&hash.encode()).cloned()
+    }
+
+    fn put(&mut self, hash: &BabyJubjubField, node: Node) {
+        self.nodes.insert(hash.encode(), node);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::key::{Prehash, Sign};
+use crate::prime_field::Error;
+use crate::{verify_challenge, BabyJubjub};
+use num_bigint::BigInt;
+use sha2::{Digest, Sha256, Sha512};
+use sp_std::vec::Vec;
+
+// Incremental signing and verification, for messages too large to hold in
+// memory. The message is fed in pieces with `update` and never copied.
+//
+// `sign` hashes the message twice, for the nonce and then for the challenge
+// which depends on the nonce, so it cannot sign in one pass. `Signer` makes
+// SHA-512 prehash signatures instead, `BabyJubjub::sign_prehashed` of
+// `Prehash::sha512(message)`, which `Verifier::new` checks. `Verifier::plain`
+// checks ordinary `sign` signatures in one pass, as R and A come before the
+// message in the challenge hash.
+
+// Makes prehash signatures only. They are not `sign` signatures: `verify`,
+// on-chain verifiers and circom circuits built for `sign` all reject them, only
+// `Verifier::new` and `BabyJubjub::verify_prehashed` accept them. Messages for
+// such verifiers have to be signed whole with `BabyJubjub::sign`.
+pub struct Signer {
+    hasher: Sha512,
+    context: Vec<u8>,
+}
+
+enum State {
+    Prehash(Sha512, Vec<u8>),
+    Plain(Sha256),
+}
+
+pub struct Verifier {
+    state: State,
+    signature: Sign<BabyJubjubField>,
+    public_key: BabyJubjubPoint,
+}
+
+impl Signer {
+    // `context` is at most 255 bytes, see `BabyJubjub::sign_ctx`.
+    pub fn new(context: &[u8]) -> Result<Self, Error> {
+        if context.len() > 255 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Signer {
+            hasher: Sha512::new(),
+            context: context.to_vec(),
+        })
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    // `BabyJubjub::sign_prehashed` of `Prehash::sha512(message)`, see `Signer`.
+    pub fn finalize(self, secret_key: &[u8]) -> Sign<BabyJubjubField> {
+        let mut digest = [0u8; 64];
+        digest.copy_from_slice(&self.hasher.finalize());
+        // cannot fail, the context length is checked in `new`
+        BabyJubjub::sign_prehashed(&Prehash::Sha512(digest), &self.context, secret_key).unwrap()
+    }
+}
+
+impl Verifier {
+    // Checks a signature of `Signer` made with the same context.
+    pub fn new(
+        context: &[u8],
+        signature: Sign<BabyJubjubField>,
+        public_key: &BabyJubjubPoint,
+    ) -> Result<Self, Error> {
+        if context.len() > 255 {
+            return Err(Error::InvalidLength);
+        }
+        Ok(Verifier {
+            state: State::Prehash(Sha512::new(), context.to_vec()),
+            signature,
+            public_key: public_key.clone(),
+        })
+    }
+
+    // Checks a signature of `BabyJubjub::sign`.
+    pub fn plain(signature: Sign<BabyJubjubField>, public_key: &BabyJubjubPoint) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(signature.r.encode());
+        hasher.update(public_key.encode());
+        Verifier {
+            state: State::Plain(hasher),
+            signature,
+            public_key: public_key.clone(),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            State::Prehash(hasher, _) => hasher.update(data),
+            State::Plain(hasher) => hasher.update(data),
+        }
+    }
+
+    pub fn finalize(self) -> bool {
+        match self.state {
+            State::Prehash(hasher, context) => {
+                let mut digest = [0u8; 64];
+                digest.copy_from_slice(&hasher.finalize());
+                BabyJubjub::verify_prehashed(&Prehash::Sha512(digest), &context, self.signature, self.public_key)
+            }
+            State::Plain(hasher) => {
+                let concat = BigInt::from_bytes_le(num_bigint::Sign::Plus, &hasher.finalize());
+                verify_challenge(concat, self.signature, self.public_key)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::EDDSA;
+
+    fn payload() -> Vec<u8> {
+        (0..10_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_prehash() {
+        let secret_key = [4u8; 32];
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let data = payload();
+
+        let mut signer = Signer::new(b"batch").unwrap();
+        for chunk in data.chunks(999) {
+            signer.update(chunk);
+        }
+        let sign = signer.finalize(&secret_key);
+        let expected = BabyJubjub::sign_prehashed(&Prehash::sha512(&data), b"batch", &secret_key).unwrap();
+        assert_eq!(sign.r, expected.r);
+        assert_eq!(sign.s, expected.s);
+
+        let mut verifier = Verifier::new(b"batch", sign, &pk).unwrap();
+        for chunk in data.chunks(4096) {
+            verifier.update(chunk);
+        }
+        assert!(verifier.finalize());
+
+        let mut verifier = Verifier::new(b"other", expected, &pk).unwrap();
+        verifier.update(&data);
+        assert!(!verifier.finalize());
+        assert!(Signer::new(&[0u8; 256]).is_err());
+    }
+
+    #[test]
+    fn test_plain() {
+        let secret_key = [4u8; 32];
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+        let data = payload();
+
+        let mut verifier = Verifier::plain(BabyJubjub::sign(&data, &secret_key), &pk);
+        for chunk in data.chunks(1000) {
+            verifier.update(chunk);
+        }
+        assert!(verifier.finalize());
+
+        let mut verifier = Verifier::plain(BabyJubjub::sign(&data, &secret_key), &pk);
+        verifier.update(&data[1..]);
+        assert!(!verifier.finalize());
+    }
+}
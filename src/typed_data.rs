@@ -0,0 +1,564 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::key::{Sign, EDDSA};
+use crate::poseidon;
+use crate::prime_field::{Encode, Error, Order, PrimeField, BN_0};
+use crate::BabyJubjub;
+use alloc::format;
+use alloc::string::String;
+use num_bigint::BigInt;
+use sha2::{Digest, Sha256};
+use sp_std::collections::btree_set::BTreeSet;
+use sp_std::vec::Vec;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use sp_std::collections::btree_map::BTreeMap;
+
+// Typed structured data signing in the spirit of EIP-712, with Poseidon in
+// place of keccak so that circuits can recompute the hash:
+//
+//   typeHash        = H(encodeType), e.g. H("Transfer(uint32 from,uint32 to,uint128 amount)")
+//   hashStruct(s)   = Poseidon*(typeHash, pack(s))
+//   domainSeparator = Poseidon*(H(DOMAIN_TYPE), H(name), H(version), chainId, verifyingContract)
+//   message hash    = Poseidon*(0x1901, domainSeparator, hashStruct(s))
+//
+// where H(str) is the first 31 bytes of SHA-256(str) read big endian and
+// Poseidon* hashes up to 16 elements at once and chains longer inputs as
+// Poseidon(Poseidon(x_0..x_15), x_16..x_30)... `pack` lays the members out in
+// order, least significant bits first, in 248 bit words: bool, uintN and address
+// members share a word while they fit, field members take a word of their own
+// and point members two (x, y). The message hash is signed with `BabyJubjub::sign`
+// over its 32 byte encoding.
+
+pub const DOMAIN_TYPE: &str = "Domain(string name,string version,uint64 chainId,address verifyingContract)";
+
+const WORD_BITS: usize = 248;
+const PREFIX: u64 = 0x1901;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldType {
+    Bool,
+    // 8 to 248 bits, by multiples of 8
+    Uint(usize),
+    Address,
+    Field,
+    Point,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Uint(BigInt),
+    Address([u8; 20]),
+    Field(BabyJubjubField),
+    Point(BabyJubjubPoint),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Member {
+    pub name: String,
+    pub ty: FieldType,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeSchema {
+    pub name: String,
+    pub members: Vec<Member>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: [u8; 20],
+}
+
+fn field(v: &BigInt) -> BabyJubjubField {
+    BabyJubjubField::new(v)
+}
+
+pub fn hash_string(s: &str) -> BabyJubjubField {
+    let digest = Sha256::digest(s.as_bytes());
+    field(&BigInt::from_bytes_be(num_bigint::Sign::Plus, &digest[..31]))
+}
+
+pub fn hash_elements(elements: &[BabyJubjubField]) -> Result<BabyJubjubField, Error> {
+    let first = elements.len().min(poseidon::MAX_INPUTS);
+    let mut h = poseidon::hash(&elements[..first])?;
+    for chunk in elements[first..].chunks(poseidon::MAX_INPUTS - 1) {
+        let inputs = [&[h][..], chunk].concat();
+        h = poseidon::hash(&inputs)?;
+    }
+    Ok(h)
+}
+
+// Type and member names go into encodeType unescaped, so they must not contain
+// its separators, or two different schemas could share a type hash.
+fn valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(|c: char| c == '(' || c == ')' || c == ',' || c.is_whitespace())
+}
+
+fn address_to_bigint(address: &[u8; 20]) -> BigInt {
+    BigInt::from_bytes_be(num_bigint::Sign::Plus, address)
+}
+
+impl FieldType {
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let ty = match s {
+            "bool" => FieldType::Bool,
+            "address" => FieldType::Address,
+            "field" => FieldType::Field,
+            "point" => FieldType::Point,
+            _ => {
+                let bits = s
+                    .strip_prefix("uint")
+                    .and_then(|bits| bits.parse::<usize>().ok())
+                    .ok_or(Error::InvalidTypedData)?;
+                if bits == 0 || bits > WORD_BITS || bits % 8 != 0 {
+                    return Err(Error::InvalidTypedData);
+                }
+                FieldType::Uint(bits)
+            }
+        };
+        Ok(ty)
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            FieldType::Bool => "bool".into(),
+            FieldType::Uint(bits) => format!("uint{}", bits),
+            FieldType::Address => "address".into(),
+            FieldType::Field => "field".into(),
+            FieldType::Point => "point".into(),
+        }
+    }
+
+    // Width in a packed word, None for the types taking whole words.
+    fn bits(&self) -> Option<usize> {
+        match self {
+            FieldType::Bool => Some(1),
+            FieldType::Uint(bits) => Some(*bits),
+            FieldType::Address => Some(160),
+            FieldType::Field | FieldType::Point => None,
+        }
+    }
+
+    // Parses the JSON form of a value: "true" or "false", decimal or 0x
+    // prefixed hex integers and field elements, 0x prefixed hex addresses and
+    // encoded points.
+    pub fn parse_value(&self, s: &str) -> Result<Value, Error> {
+        let value = match self {
+            FieldType::Bool => match s {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => return Err(Error::InvalidTypedData),
+            },
+            FieldType::Uint(_) => Value::Uint(parse_integer(s)?),
+            FieldType::Field => Value::Field(field(&parse_integer(s)?)),
+            FieldType::Address => {
+                let mut address = [0u8; 20];
+                address.copy_from_slice(&parse_hex_bytes(s, 20)?);
+                Value::Address(address)
+            }
+            FieldType::Point => Value::Point(BabyJubjubPoint::decode(&parse_hex_bytes(s, 32)?)?),
+        };
+        Ok(value)
+    }
+}
+
+fn parse_integer(s: &str) -> Result<BigInt, Error> {
+    let v = match s.strip_prefix("0x") {
+        Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+        None => BigInt::parse_bytes(s.as_bytes(), 10),
+    };
+    v.ok_or(Error::InvalidTypedData)
+}
+
+fn parse_hex_bytes(s: &str, len: usize) -> Result<Vec<u8>, Error> {
+    let hex = s.strip_prefix("0x").ok_or(Error::InvalidTypedData)?;
+    if hex.len() != 2 * len {
+        return Err(Error::InvalidLength);
+    }
+    (0..len)
+        .map(|i| {
+            let byte = hex.get(2 * i..2 * i + 2).ok_or(Error::InvalidTypedData)?;
+            u8::from_str_radix(byte, 16).map_err(|_| Error::InvalidTypedData)
+        })
+        .collect()
+}
+
+fn flush(words: &mut Vec<BabyJubjubField>, word: &mut BigInt, used: &mut usize) {
+    if *used > 0 {
+        words.push(field(word));
+        *word = BN_0.clone();
+        *used = 0;
+    }
+}
+
+impl TypeSchema {
+    // `members` are (name, type) pairs, e.g. ("amount", "uint128"). Names must
+    // be non empty, distinct and free of parentheses, commas and whitespace.
+    pub fn new(name: &str, members: &[(&str, &str)]) -> Result<Self, Error> {
+        let names: BTreeSet<&str> = members.iter().map(|(name, _)| *name).collect();
+        if !valid_name(name) || names.len() != members.len() || !names.iter().all(|name| valid_name(name)) {
+            return Err(Error::InvalidTypedData);
+        }
+        let members = members
+            .iter()
+            .map(|(name, ty)| {
+                Ok(Member {
+                    name: (*name).into(),
+                    ty: FieldType::parse(ty)?,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(TypeSchema {
+            name: name.into(),
+            members,
+        })
+    }
+
+    pub fn encode_type(&self) -> String {
+        let members = self
+            .members
+            .iter()
+            .map(|m| format!("{} {}", m.ty.name(), m.name))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{}({})", self.name, members)
+    }
+
+    pub fn type_hash(&self) -> BabyJubjubField {
+        hash_string(&self.encode_type())
+    }
+
+    // `values` are in the order of the members.
+    pub fn pack(&self, values: &[Value]) -> Result<Vec<BabyJubjubField>, Error> {
+        if values.len() != self.members.len() {
+            return Err(Error::InvalidTypedData);
+        }
+
+        let mut words = Vec::new();
+        let mut word = BN_0.clone();
+        let mut used = 0;
+        for (member, value) in self.members.iter().zip(values.iter()) {
+            let v = match (member.ty, value) {
+                (FieldType::Bool, Value::Bool(b)) => BigInt::from(*b as u8),
+                (FieldType::Uint(bits), Value::Uint(v)) => {
+                    if v < &BN_0 || v.bits() > bits as u64 {
+                        return Err(Error::InvalidTypedData);
+                    }
+                    v.clone()
+                }
+                (FieldType::Address, Value::Address(a)) => address_to_bigint(a),
+                (FieldType::Field, Value::Field(f)) if &f.v < BabyJubjubField::order() => {
+                    flush(&mut words, &mut word, &mut used);
+                    words.push(f.clone());
+                    continue;
+                }
+                (FieldType::Point, Value::Point(p)) => {
+                    flush(&mut words, &mut word, &mut used);
+                    words.push(p.x.clone());
+                    words.push(p.y.clone());
+                    continue;
+                }
+                _ => return Err(Error::InvalidTypedData),
+            };
+
+            // cannot fail, only packable types are left
+            let bits = member.ty.bits().unwrap();
+            if used + bits > WORD_BITS {
+                flush(&mut words, &mut word, &mut used);
+            }
+            word += v << used;
+            used += bits;
+        }
+        flush(&mut words, &mut word, &mut used);
+        Ok(words)
+    }
+
+    pub fn hash_struct(&self, values: &[Value]) -> Result<BabyJubjubField, Error> {
+        let elements = [&[self.type_hash()][..], &self.pack(values)?].concat();
+        hash_elements(&elements)
+    }
+}
+
+impl Domain {
+    pub fn separator(&self) -> BabyJubjubField {
+        let elements = [
+            hash_string(DOMAIN_TYPE),
+            hash_string(&self.name),
+            hash_string(&self.version),
+            field(&BigInt::from(self.chain_id)),
+            field(&address_to_bigint(&self.verifying_contract)),
+        ];
+        // cannot fail, there are 5 elements
+        hash_elements(&elements).unwrap()
+    }
+}
+
+pub fn hash_typed_data(domain: &Domain, schema: &TypeSchema, values: &[Value]) -> Result<BabyJubjubField, Error> {
+    let prefix = field(&BigInt::from(PREFIX));
+    hash_elements(&[prefix, domain.separator(), schema.hash_struct(values)?])
+}
+
+pub fn sign(
+    domain: &Domain,
+    schema: &TypeSchema,
+    values: &[Value],
+    secret_key: &[u8],
+) -> Result<Sign<BabyJubjubField>, Error> {
+    let hash = hash_typed_data(domain, schema, values)?;
+    Ok(BabyJubjub::sign(&hash.encode(), secret_key))
+}
+
+pub fn verify(
+    domain: &Domain,
+    schema: &TypeSchema,
+    values: &[Value],
+    signature: &Sign<BabyJubjubField>,
+    public_key: &BabyJubjubPoint,
+) -> bool {
+    let signature = Sign {
+        r: signature.r.clone(),
+        s: signature.s.clone(),
+    };
+    match hash_typed_data(domain, schema, values) {
+        Ok(hash) => BabyJubjub::verify(&hash.encode(), signature, public_key.clone()),
+        Err(_) => false,
+    }
+}
+
+// JSON form, as passed from JS:
+//
+//   {
+//     "domain": { "name": "...", "version": "1", "chainId": 1, "verifyingContract": "0x..." },
+//     "primaryType": "Transfer",
+//     "types": [{ "name": "from", "type": "uint32" }, ...],
+//     "message": { "from": "1", ... }
+//   }
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedDataJson {
+    pub domain: DomainJson,
+    pub primary_type: String,
+    pub types: Vec<MemberJson>,
+    pub message: BTreeMap<String, String>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DomainJson {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: String,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemberJson {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[cfg(feature = "serde")]
+impl TypedDataJson {
+    pub fn parse(&self) -> Result<(Domain, TypeSchema, Vec<Value>), Error> {
+        let mut verifying_contract = [0u8; 20];
+        verifying_contract.copy_from_slice(&parse_hex_bytes(&self.domain.verifying_contract, 20)?);
+        let domain = Domain {
+            name: self.domain.name.clone(),
+            version: self.domain.version.clone(),
+            chain_id: self.domain.chain_id,
+            verifying_contract,
+        };
+
+        let members = self
+            .types
+            .iter()
+            .map(|m| (m.name.as_str(), m.ty.as_str()))
+            .collect::<Vec<_>>();
+        let schema = TypeSchema::new(&self.primary_type, &members)?;
+
+        if self.message.len() != schema.members.len() {
+            return Err(Error::InvalidTypedData);
+        }
+        let values = schema
+            .members
+            .iter()
+            .map(|m| m.ty.parse_value(self.message.get(&m.name).ok_or(Error::InvalidTypedData)?))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok((domain, schema, values))
+    }
+
+    pub fn hash(&self) -> Result<BabyJubjubField, Error> {
+        let (domain, schema, values) = self.parse()?;
+        hash_typed_data(&domain, &schema, &values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prime_field::BN_1;
+
+    fn domain() -> Domain {
+        Domain {
+            name: "Delphinus L2".into(),
+            version: "1".into(),
+            chain_id: 7,
+            verifying_contract: [0xab; 20],
+        }
+    }
+
+    fn transfer() -> (TypeSchema, Vec<Value>) {
+        let schema = TypeSchema::new(
+            "Transfer",
+            &[("from", "uint32"), ("to", "uint32"), ("token", "address"), ("amount", "uint128"), ("nonce", "uint64")],
+        )
+        .unwrap();
+        let values = [
+            Value::Uint(BigInt::from(1u64)),
+            Value::Uint(BigInt::from(2u64)),
+            Value::Address([0x11; 20]),
+            Value::Uint(BigInt::from(10u64).pow(18)),
+            Value::Uint(BigInt::from(5u64)),
+        ];
+        (schema, values.to_vec())
+    }
+
+    #[test]
+    fn test_pack() {
+        let (schema, values) = transfer();
+        assert_eq!(
+            schema.encode_type(),
+            "Transfer(uint32 from,uint32 to,address token,uint128 amount,uint64 nonce)"
+        );
+
+        // from, to and token share the first word, amount and nonce the second
+        let words = schema.pack(&values).unwrap();
+        let first = BigInt::from(1u64) + (BigInt::from(2u64) << 32) + (address_to_bigint(&[0x11; 20]) << 64);
+        let second = BigInt::from(10u64).pow(18) + (BigInt::from(5u64) << 128);
+        assert_eq!(words, [field(&first), field(&second)]);
+
+        let mut bad = values.clone();
+        bad[0] = Value::Uint(BigInt::from(1u64) << 32);
+        assert_eq!(schema.pack(&bad), Err(Error::InvalidTypedData));
+        bad[0] = Value::Bool(true);
+        assert_eq!(schema.pack(&bad), Err(Error::InvalidTypedData));
+        assert_eq!(schema.pack(&values[1..]), Err(Error::InvalidTypedData));
+        assert_eq!(FieldType::parse("uint256"), Err(Error::InvalidTypedData));
+
+        // names that would make encodeType ambiguous
+        assert!(TypeSchema::new("T", &[("x,uint32 y", "uint32")]).is_err());
+        assert!(TypeSchema::new("T", &[("x", "uint32"), ("x", "uint32")]).is_err());
+        assert!(TypeSchema::new("T(uint32 x)", &[("y", "uint32")]).is_err());
+        assert!(TypeSchema::new("T", &[("", "uint32")]).is_err());
+        assert!(TypeSchema::new("T", &[("x", "uint32"), ("y", "uint32")]).is_ok());
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let (schema, values) = transfer();
+        let secret_key = [3u8; 32];
+        let pk = BabyJubjub::pubkey_from_secretkey(&secret_key);
+
+        // reference values for other implementations
+        let decimal = |s: &str| field(&BigInt::parse_bytes(s.as_bytes(), 10).unwrap());
+        assert_eq!(
+            schema.type_hash(),
+            decimal("116073212116433792107862371326412478362025605827024956168720640528854102797")
+        );
+        assert_eq!(
+            domain().separator(),
+            decimal("18238352516780185794860399999286290173998310325513408243579862816485524189353")
+        );
+        assert_eq!(
+            schema.hash_struct(&values).unwrap(),
+            decimal("18098562760738291005503679526185982232478836529377082466406810860053125798134")
+        );
+        assert_eq!(
+            hash_typed_data(&domain(), &schema, &values).unwrap(),
+            decimal("2232083419305549100802502330880424044953651504148216081130099584097175652519")
+        );
+
+        let sig = sign(&domain(), &schema, &values, &secret_key).unwrap();
+        assert!(verify(&domain(), &schema, &values, &sig, &pk));
+
+        // the chain id binds the signature to a deployment
+        let mut other = domain();
+        other.chain_id = 8;
+        assert!(!verify(&other, &schema, &values, &sig, &pk));
+
+        let mut tampered = values;
+        tampered[3] = Value::Uint(BigInt::from(1u64));
+        assert!(!verify(&domain(), &schema, &tampered, &sig, &pk));
+    }
+
+    #[test]
+    fn test_long_struct() {
+        let members = (0..20).map(|i| format!("f{}", i)).collect::<Vec<_>>();
+        let pairs = members.iter().map(|m| (m.as_str(), "field")).collect::<Vec<_>>();
+        let schema = TypeSchema::new("Batch", &pairs).unwrap();
+        let values = (0..20u64).map(|i| Value::Field(field(&BigInt::from(i)))).collect::<Vec<_>>();
+
+        let elements = [&[schema.type_hash()][..], &schema.pack(&values).unwrap()].concat();
+        assert_eq!(elements.len(), 21);
+        let chained = poseidon::hash(&[&[poseidon::hash(&elements[..16]).unwrap()][..], &elements[16..]].concat()).unwrap();
+        assert_eq!(schema.hash_struct(&values).unwrap(), chained);
+
+        // a point closes the word of the bools before it
+        let point = Value::Point(BabyJubjubPoint::get_basepoint().clone());
+        let schema = TypeSchema::new("Key", &[("active", "bool"), ("owner", "point"), ("admin", "bool")]).unwrap();
+        let words = schema.pack(&[Value::Bool(true), point, Value::Bool(false)]).unwrap();
+        assert_eq!(words.len(), 4);
+        assert_eq!(words[0], field(&BN_1));
+        assert_eq!(words[3], field(&BN_0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json() {
+        let json = r#"{
+            "domain": {
+                "name": "Delphinus L2",
+                "version": "1",
+                "chainId": 7,
+                "verifyingContract": "0xabababababababababababababababababababab"
+            },
+            "primaryType": "Transfer",
+            "types": [
+                { "name": "from", "type": "uint32" },
+                { "name": "to", "type": "uint32" },
+                { "name": "token", "type": "address" },
+                { "name": "amount", "type": "uint128" },
+                { "name": "nonce", "type": "uint64" }
+            ],
+            "message": {
+                "from": "1",
+                "to": "0x2",
+                "token": "0x1111111111111111111111111111111111111111",
+                "amount": "1000000000000000000",
+                "nonce": "5"
+            }
+        }"#;
+        let data: TypedDataJson = serde_json::from_str(json).unwrap();
+        let (schema, values) = transfer();
+        assert_eq!(data.hash().unwrap(), hash_typed_data(&domain(), &schema, &values).unwrap());
+
+        let mut missing = data.clone();
+        missing.message.remove("nonce");
+        assert_eq!(missing.hash(), Err(Error::InvalidTypedData));
+        let mut bad = data;
+        bad.message.insert("token".into(), "0x11".into());
+        assert_eq!(bad.hash(), Err(Error::InvalidLength));
+    }
+}
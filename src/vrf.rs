@@ -0,0 +1,258 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::curve::Curve;
+use crate::hash_to_curve::{self, hash_to_scalar, SUITE_RO};
+use crate::poseidon;
+use crate::prime_field::{modulus, Encode, Error, Order, PrimeField, BN_0};
+use crate::schnorr;
+use num_bigint::{BigInt, Sign};
+use sha2::{Digest, Sha256};
+use sp_std::vec::Vec;
+
+// ECVRF (RFC 9381 section 5) over the prime order subgroup of BabyJubjub:
+//
+//   H = hash_to_curve(Y || alpha), Gamma = x * H,
+//   c = challenge(Y, H, Gamma, k * B, k * H), s = k + c * x,
+//   pi = Gamma || c || s, beta = proof_to_hash(8 * Gamma).
+//
+// hash_to_curve is the crate's Elligator 2 random oracle encoding with
+// DST = "ECVRF_" || SUITE_RO || suite_string. The nonce k is derived from the
+// secret scalar and H instead of RFC 8032 key expansion, since keys here are
+// scalars. The SHA-256 suite follows the RFC encodings with a 16 byte
+// challenge, the Poseidon suite hashes coordinates so that `verify` and
+// `proof_to_hash` are cheap in circuits.
+
+pub trait Suite {
+    const SUITE_STRING: &'static [u8];
+
+    // byte length of c in the proof
+    const C_LEN: usize;
+
+    type Output;
+
+    fn challenge(points: &[&BabyJubjubPoint; 5]) -> BigInt;
+
+    // `gamma` is already multiplied by the cofactor
+    fn hash_point(gamma: &BabyJubjubPoint) -> Self::Output;
+}
+
+pub struct Sha256Suite;
+
+pub struct PoseidonSuite;
+
+impl Suite for Sha256Suite {
+    const SUITE_STRING: &'static [u8] = b"ECVRF-BABYJUBJUB-SHA256-ELL2";
+    const C_LEN: usize = 16;
+
+    type Output = [u8; 32];
+
+    // truncated SHA-256(suite_string || 0x02 || points || 0x00), little endian
+    fn challenge(points: &[&BabyJubjubPoint; 5]) -> BigInt {
+        let mut hasher = Sha256::new().chain(Self::SUITE_STRING).chain([0x02u8]);
+        for p in points.iter() {
+            hasher = hasher.chain(p.encode());
+        }
+        let h = hasher.chain([0x00u8]).finalize();
+        BigInt::from_bytes_le(Sign::Plus, &h[..Self::C_LEN])
+    }
+
+    // SHA-256(suite_string || 0x03 || gamma || 0x00)
+    fn hash_point(gamma: &BabyJubjubPoint) -> [u8; 32] {
+        Sha256::new()
+            .chain(Self::SUITE_STRING)
+            .chain([0x03u8])
+            .chain(gamma.encode())
+            .chain([0x00u8])
+            .finalize()
+            .into()
+    }
+}
+
+impl Suite for PoseidonSuite {
+    const SUITE_STRING: &'static [u8] = b"ECVRF-BABYJUBJUB-POSEIDON-ELL2";
+    const C_LEN: usize = 32;
+
+    type Output = BabyJubjubField;
+
+    // Poseidon(Y.x, Y.y, H.x, H.y, ..., V.x, V.y) mod l
+    fn challenge(points: &[&BabyJubjubPoint; 5]) -> BigInt {
+        let inputs: Vec<BabyJubjubField> = points
+            .iter()
+            .flat_map(|p| [p.x.clone(), p.y.clone()])
+            .collect();
+        poseidon::hash(&inputs).unwrap().v % BabyJubjubField::suborder()
+    }
+
+    // Poseidon(gamma.x, gamma.y)
+    fn hash_point(gamma: &BabyJubjubPoint) -> BabyJubjubField {
+        poseidon::hash(&[gamma.x.clone(), gamma.y.clone()]).unwrap()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proof {
+    pub gamma: BabyJubjubPoint,
+    pub c: BigInt,
+    pub s: BigInt,
+}
+
+impl Proof {
+    // gamma || c (C_LEN bytes, little endian) || s
+    pub fn encode<S: Suite>(&self) -> Vec<u8> {
+        let c = BabyJubjubField::new(&self.c).encode();
+        [&self.gamma.encode()[..], &c[..S::C_LEN], &BabyJubjubField::new(&self.s).encode()].concat()
+    }
+
+    pub fn decode<S: Suite>(encode: &[u8]) -> Result<Self, Error> {
+        if encode.len() != 64 + S::C_LEN {
+            return Err(Error::InvalidLength);
+        }
+        let gamma = BabyJubjubPoint::decode(&encode[..32])?;
+        let c = BigInt::from_bytes_le(Sign::Plus, &encode[32..32 + S::C_LEN]);
+        let s = BigInt::from_bytes_le(Sign::Plus, &encode[32 + S::C_LEN..]);
+        if &c >= BabyJubjubField::suborder() || &s >= BabyJubjubField::suborder() {
+            return Err(Error::InvalidScalar);
+        }
+        Ok(Proof { gamma, c, s })
+    }
+}
+
+fn encode_to_curve<S: Suite>(public_key: &BabyJubjubPoint, alpha: &[u8]) -> BabyJubjubPoint {
+    let dst = [b"ECVRF_", SUITE_RO, S::SUITE_STRING].concat();
+    let msg = [&public_key.encode()[..], alpha].concat();
+    // cannot fail, the length is fixed
+    hash_to_curve::hash_to_curve(&msg, &dst).unwrap()
+}
+
+fn nonce_generation<S: Suite>(secret: &BigInt, h: &BabyJubjubPoint) -> BigInt {
+    let dst = [b"ECVRF_", S::SUITE_STRING, b"_nonce"].concat();
+    let msg = [&BabyJubjubField::new(secret).encode()[..], &h.encode()].concat();
+    hash_to_scalar(&msg, &dst)
+}
+
+pub fn prove<S: Suite>(secret: &BigInt, alpha: &[u8]) -> Proof {
+    let l = BabyJubjubField::suborder();
+    let x = modulus(secret, l);
+    let y = schnorr::public_key(&x);
+
+    let h = encode_to_curve::<S>(&y, alpha);
+    let gamma = &h * &x;
+    let k = nonce_generation::<S>(&x, &h);
+    let u = BabyJubjubPoint::get_basepoint() * &k;
+    let v = &h * &k;
+    let c = S::challenge(&[&y, &h, &gamma, &u, &v]);
+    let s = (k + &c * x) % l;
+    Proof { gamma, c, s }
+}
+
+pub fn proof_to_hash<S: Suite>(proof: &Proof) -> S::Output {
+    S::hash_point(&hash_to_curve::clear_cofactor(&proof.gamma))
+}
+
+// Returns beta for a valid proof.
+pub fn verify<S: Suite>(public_key: &BabyJubjubPoint, alpha: &[u8], proof: &Proof) -> Option<S::Output> {
+    let origin = BabyJubjubPoint::get_origin();
+    // reject low order keys, as in RFC 9381 ECVRF_validate_key
+    if &hash_to_curve::clear_cofactor(public_key) == origin {
+        return None;
+    }
+    if proof.c < *BN_0 || proof.s < *BN_0 || &proof.s >= BabyJubjubField::suborder() {
+        return None;
+    }
+
+    let h = encode_to_curve::<S>(public_key, alpha);
+    let u = BabyJubjubPoint::get_basepoint() * &proof.s - public_key * &proof.c;
+    let v = &h * &proof.s - &proof.gamma * &proof.c;
+    let c = S::challenge(&[public_key, &h, &proof.gamma, &u, &v]);
+    if c == proof.c {
+        Some(proof_to_hash::<S>(proof))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_suite() {
+        let secret = BigInt::from(0x5eed_u64);
+        let pk = schnorr::public_key(&secret);
+
+        let proof = prove::<Sha256Suite>(&secret, b"block 1000");
+        let beta = verify::<Sha256Suite>(&pk, b"block 1000", &proof).unwrap();
+        assert_eq!(beta, proof_to_hash::<Sha256Suite>(&proof));
+        assert_eq!(proof, prove::<Sha256Suite>(&secret, b"block 1000"));
+
+        assert!(verify::<Sha256Suite>(&pk, b"block 1001", &proof).is_none());
+        assert!(verify::<Sha256Suite>(&schnorr::public_key(&BigInt::from(2u64)), b"block 1000", &proof).is_none());
+        assert_ne!(proof_to_hash::<Sha256Suite>(&prove::<Sha256Suite>(&secret, b"block 1001")), beta);
+
+        let encode = proof.encode::<Sha256Suite>();
+        assert_eq!(encode.len(), 80);
+        assert_eq!(Proof::decode::<Sha256Suite>(&encode).unwrap(), proof);
+    }
+
+    #[test]
+    fn test_poseidon_suite() {
+        let secret = BigInt::from(0x5eed_u64);
+        let pk = schnorr::public_key(&secret);
+
+        let proof = prove::<PoseidonSuite>(&secret, b"block 1000");
+        let beta = verify::<PoseidonSuite>(&pk, b"block 1000", &proof).unwrap();
+        assert_eq!(beta, proof_to_hash::<PoseidonSuite>(&proof));
+
+        let mut bad = proof.clone();
+        bad.s = (bad.s + 1u64) % BabyJubjubField::suborder();
+        assert!(verify::<PoseidonSuite>(&pk, b"block 1000", &bad).is_none());
+
+        let mut bad = proof.clone();
+        bad.gamma = &bad.gamma + BabyJubjubPoint::get_basepoint();
+        assert!(verify::<PoseidonSuite>(&pk, b"block 1000", &bad).is_none());
+
+        let encode = proof.encode::<PoseidonSuite>();
+        assert_eq!(encode.len(), 96);
+        assert_eq!(Proof::decode::<PoseidonSuite>(&encode).unwrap(), proof);
+    }
+
+    #[test]
+    fn test_low_order_key() {
+        let secret = BigInt::from(7u64);
+        let proof = prove::<Sha256Suite>(&secret, b"");
+        let origin = BabyJubjubPoint::get_origin();
+        assert!(verify::<Sha256Suite>(origin, b"", &proof).is_none());
+    }
+
+    // Regression vectors: pi and beta for a fixed key, pinning the suite
+    // encodings across releases.
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_vectors() {
+        use rustc_hex::ToHex;
+
+        let secret = BigInt::from(1u64);
+
+        let proof = prove::<Sha256Suite>(&secret, b"");
+        let encoded: String = proof.encode::<Sha256Suite>().to_hex();
+        assert_eq!(
+            encoded,
+            "d19eb31b3491aa3ce8402bb05dd3eabd077934e5dcbf0def5ca02fdb8418990a\
+             41c0eee6bf80cc742db920bb9db419e8\
+             4a6d50e033e82d5ae72ae47c5621441a45754fd15565a38f37c9529658334000"
+        );
+        let hash: String = proof_to_hash::<Sha256Suite>(&proof).to_hex();
+        assert_eq!(
+            hash,
+            "ece75827b9fd09a4cf6bf509aad2a2abe0590d2449b693dc772df37f4e7ee44d"
+        );
+
+        let proof = prove::<PoseidonSuite>(&secret, b"sample");
+        assert!(verify::<PoseidonSuite>(&schnorr::public_key(&secret), b"sample", &proof).is_some());
+        let hash: String = proof_to_hash::<PoseidonSuite>(&proof).encode().to_hex();
+        assert_eq!(
+            hash,
+            "2579aa6320dd344431e8d09586b2c76212e75381cd3dec97f0ac38b782f0fc02"
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use crate::babyjubjub::BabyJubjubField;
+use crate::babyjubjub_point::BabyJubjubPoint;
+use crate::montgomery::{self, MontgomeryPoint};
+use crate::prime_field::{Error, PrimeField};
+use num_bigint::BigInt;
+use sp_std::convert::TryFrom;
+
+// BabyJubjub in short Weierstrass form y^2 = x^3 + a * x + b, reached through
+// the Montgomery form: x = u / B + A / (3 * B), y = v / B, with
+// a = (3 - A^2) / (3 * B^2) and b = (2 * A^3 - 9 * A) / (27 * B^3).
+
+lazy_static! {
+    static ref A_OVER_3: BabyJubjubField = &*montgomery::A / BabyJubjubField::new(&BigInt::from(3));
+
+    pub static ref A: BabyJubjubField = {
+        let (ma, mb) = (&*montgomery::A, &*montgomery::B);
+        (BabyJubjubField::new(&BigInt::from(3)) - ma * ma) / (BabyJubjubField::new(&BigInt::from(3)) * mb * mb)
+    };
+
+    pub static ref B: BabyJubjubField = {
+        let (ma, mb) = (&*montgomery::A, &*montgomery::B);
+        (BabyJubjubField::new(&BigInt::from(2)) * ma * ma * ma - BabyJubjubField::new(&BigInt::from(9)) * ma)
+            / (BabyJubjubField::new(&BigInt::from(27)) * mb * mb * mb)
+    };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum WeierstrassPoint {
+    Infinity,
+    Affine { x: BabyJubjubField, y: BabyJubjubField },
+}
+
+impl WeierstrassPoint {
+    pub fn is_on_curve(&self) -> bool {
+        match self {
+            WeierstrassPoint::Infinity => true,
+            WeierstrassPoint::Affine { x, y } => y * y == x * x * x + &*A * x + &*B,
+        }
+    }
+}
+
+impl<'a> From<&'a MontgomeryPoint> for WeierstrassPoint {
+    fn from(p: &MontgomeryPoint) -> WeierstrassPoint {
+        match p {
+            MontgomeryPoint::Infinity => WeierstrassPoint::Infinity,
+            MontgomeryPoint::Affine { u, v } => WeierstrassPoint::Affine {
+                x: u / &*montgomery::B + &*A_OVER_3,
+                y: v / &*montgomery::B,
+            },
+        }
+    }
+}
+
+impl<'a> From<&'a WeierstrassPoint> for MontgomeryPoint {
+    fn from(p: &WeierstrassPoint) -> MontgomeryPoint {
+        match p {
+            WeierstrassPoint::Infinity => MontgomeryPoint::Infinity,
+            WeierstrassPoint::Affine { x, y } => MontgomeryPoint::Affine {
+                u: (x - &*A_OVER_3) * &*montgomery::B,
+                v: y * &*montgomery::B,
+            },
+        }
+    }
+}
+
+impl<'a> From<&'a BabyJubjubPoint> for WeierstrassPoint {
+    fn from(p: &BabyJubjubPoint) -> WeierstrassPoint {
+        WeierstrassPoint::from(&MontgomeryPoint::from(p))
+    }
+}
+
+// Fails with `InvalidPoint` for points off the curve.
+impl<'a> TryFrom<&'a WeierstrassPoint> for BabyJubjubPoint {
+    type Error = Error;
+
+    fn try_from(p: &WeierstrassPoint) -> Result<BabyJubjubPoint, Error> {
+        BabyJubjubPoint::try_from(&MontgomeryPoint::from(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::Curve;
+    use crate::prime_field::{BN_0, BN_1};
+
+    fn field(s: &str) -> BabyJubjubField {
+        BabyJubjubField::new(&BigInt::parse_bytes(s.as_bytes(), 10).unwrap())
+    }
+
+    #[test]
+    fn test_coefficients() {
+        // EIP-2494
+        assert_eq!(
+            *A,
+            field("7296080957279758407415468581752425029516121466805344781232734728849116493472")
+        );
+        assert_eq!(
+            *B,
+            field("16213513238399463127589930181672055621146936592900766180517188641980520820846")
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for k in [1u64, 2, 5, 1 << 33, u64::MAX].iter() {
+            let p = BabyJubjubPoint::get_basepoint() * BigInt::from(*k);
+            let w = WeierstrassPoint::from(&p);
+            assert!(w.is_on_curve());
+            assert_eq!(BabyJubjubPoint::try_from(&w).unwrap(), p);
+        }
+    }
+
+    #[test]
+    fn test_exceptional_points() {
+        let origin = BabyJubjubPoint::get_origin();
+        assert_eq!(WeierstrassPoint::from(origin), WeierstrassPoint::Infinity);
+        assert_eq!(BabyJubjubPoint::try_from(&WeierstrassPoint::Infinity).unwrap(), *origin);
+
+        let zero = BabyJubjubField::new(&BN_0);
+        let torsion = BabyJubjubPoint {
+            x: zero.clone(),
+            y: &zero - BabyJubjubField::new(&BN_1),
+        };
+        let w = WeierstrassPoint::from(&torsion);
+        assert_eq!(w, WeierstrassPoint::Affine { x: A_OVER_3.clone(), y: zero });
+        assert!(w.is_on_curve());
+        assert_eq!(BabyJubjubPoint::try_from(&w).unwrap(), torsion);
+
+        let off = WeierstrassPoint::Affine { x: BabyJubjubField::new(&BN_0), y: BabyJubjubField::new(&BN_0) };
+        assert_eq!(BabyJubjubPoint::try_from(&off), Err(Error::InvalidPoint));
+    }
+}